@@ -0,0 +1,199 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use eyre::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    Int16,
+    Int24,
+    Int32,
+    Float32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    Wav,
+    /// Headerless little endian samples
+    RawPcm,
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteAudioOptions {
+    pub format: SampleFormat,
+    /// Number of interleaved channels in the samples passed to the writer
+    pub channels: u16,
+    pub container: Container,
+    /// Add TPDF dither when quantizing to integer formats
+    pub dither: bool,
+}
+
+impl Default for WriteAudioOptions {
+    fn default() -> Self {
+        Self {
+            format: SampleFormat::Int16,
+            channels: 1,
+            container: Container::Wav,
+            dither: false,
+        }
+    }
+}
+
+enum Output {
+    Wav(hound::WavWriter<BufWriter<File>>),
+    Raw(BufWriter<File>),
+}
+
+/// Incremental audio writer, eg. for chunks coming from TTS.
+///
+/// The file is finalized by [`AudioWriter::finalize`] or when the writer is dropped.
+pub struct AudioWriter {
+    output: Option<Output>,
+    format: SampleFormat,
+    channels: u16,
+    dither: Option<Dither>,
+}
+
+impl AudioWriter {
+    pub fn create(path: &str, sample_rate: u32, options: WriteAudioOptions) -> Result<Self> {
+        if options.channels == 0 {
+            bail!("channels must be greater than 0");
+        }
+        let output = match options.container {
+            Container::Wav => {
+                let (bits_per_sample, sample_format) = match options.format {
+                    SampleFormat::Int16 => (16, hound::SampleFormat::Int),
+                    SampleFormat::Int24 => (24, hound::SampleFormat::Int),
+                    SampleFormat::Int32 => (32, hound::SampleFormat::Int),
+                    SampleFormat::Float32 => (32, hound::SampleFormat::Float),
+                };
+                let spec = hound::WavSpec {
+                    channels: options.channels,
+                    sample_rate,
+                    bits_per_sample,
+                    sample_format,
+                };
+                Output::Wav(hound::WavWriter::create(path, spec)?)
+            }
+            Container::RawPcm => Output::Raw(BufWriter::new(File::create(path)?)),
+        };
+        let dither = (options.dither && options.format != SampleFormat::Float32).then(Dither::new);
+
+        Ok(Self {
+            output: Some(output),
+            format: options.format,
+            channels: options.channels,
+            dither,
+        })
+    }
+
+    /// Write interleaved samples
+    pub fn write(&mut self, samples: &[f32]) -> Result<()> {
+        for &sample in samples {
+            self.write_sample(sample)?;
+        }
+        Ok(())
+    }
+
+    /// Write one buffer per channel, interleaving them
+    pub fn write_planar(&mut self, channels: &[&[f32]]) -> Result<()> {
+        if channels.len() != self.channels as usize {
+            bail!(
+                "expected {} channels but got {}",
+                self.channels,
+                channels.len()
+            );
+        }
+        let len = channels
+            .iter()
+            .map(|channel| channel.len())
+            .min()
+            .unwrap_or(0);
+        for i in 0..len {
+            for channel in channels {
+                self.write_sample(channel[i])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finalize(mut self) -> Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        match self.output.take() {
+            Some(Output::Wav(writer)) => writer.finalize()?,
+            Some(Output::Raw(mut writer)) => writer.flush()?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn quantize(&mut self, sample: f32, max: f64) -> i32 {
+        let noise = self.dither.as_mut().map(|d| d.next()).unwrap_or(0.0);
+        (sample as f64 * max + noise).round().clamp(-max - 1.0, max) as i32
+    }
+
+    fn write_sample(&mut self, sample: f32) -> Result<()> {
+        let format = self.format;
+        let value = match format {
+            SampleFormat::Int16 => self.quantize(sample, i16::MAX as f64),
+            SampleFormat::Int24 => self.quantize(sample, 8_388_607.0),
+            SampleFormat::Int32 => self.quantize(sample, i32::MAX as f64),
+            SampleFormat::Float32 => 0,
+        };
+        let Some(output) = self.output.as_mut() else {
+            bail!("writer is already finalized");
+        };
+        match output {
+            Output::Wav(writer) => match format {
+                SampleFormat::Int16 => writer.write_sample(value as i16)?,
+                SampleFormat::Int24 | SampleFormat::Int32 => writer.write_sample(value)?,
+                SampleFormat::Float32 => writer.write_sample(sample)?,
+            },
+            Output::Raw(writer) => match format {
+                SampleFormat::Int16 => writer.write_all(&(value as i16).to_le_bytes())?,
+                SampleFormat::Int24 => writer.write_all(&value.to_le_bytes()[..3])?,
+                SampleFormat::Int32 => writer.write_all(&value.to_le_bytes())?,
+                SampleFormat::Float32 => writer.write_all(&sample.to_le_bytes())?,
+            },
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AudioWriter {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            tracing::error!("failed to finalize audio file: {}", err);
+        }
+    }
+}
+
+/// Triangular dither noise of +-1 LSB
+struct Dither {
+    state: u64,
+}
+
+impl Dither {
+    fn new() -> Self {
+        Self {
+            state: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    fn uniform(&mut self) -> f64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next(&mut self) -> f64 {
+        self.uniform() - self.uniform()
+    }
+}
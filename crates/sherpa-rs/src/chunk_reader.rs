@@ -0,0 +1,120 @@
+use std::{fs::File, io::BufReader};
+
+use eyre::{bail, Result};
+
+use crate::{
+    resampler::Resampler, select_channels, wav_int_scale, ChannelSelection, ReadAudioOptions,
+};
+
+enum WavSamples {
+    Int(hound::WavIntoSamples<BufReader<File>, i32>, f32),
+    Float(hound::WavIntoSamples<BufReader<File>, f32>),
+}
+
+impl WavSamples {
+    fn read(&mut self, n: usize, out: &mut Vec<f32>) -> Result<()> {
+        match self {
+            WavSamples::Int(samples, scale) => {
+                for sample in samples.by_ref().take(n) {
+                    out.push(sample? as f32 / *scale);
+                }
+            }
+            WavSamples::Float(samples) => {
+                for sample in samples.by_ref().take(n) {
+                    out.push(sample?);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a wav file lazily as mono chunks of `chunk_size` samples.
+///
+/// Only a few chunks are kept in memory, so it can feed `SileroVad::accept_waveform`
+/// with recordings of any length. The last chunk may be shorter.
+pub struct ChunkReader {
+    samples: WavSamples,
+    channels: u16,
+    selection: ChannelSelection,
+    resampler: Option<Resampler>,
+    sample_rate: u32,
+    chunk_size: usize,
+    pending: Vec<f32>,
+    finished: bool,
+}
+
+impl ChunkReader {
+    pub fn open(path: &str, chunk_size: usize, options: ReadAudioOptions) -> Result<Self> {
+        if chunk_size == 0 {
+            bail!("chunk size must be greater than 0");
+        }
+        let reader = hound::WavReader::open(path)?;
+        let spec = reader.spec();
+        let samples = match wav_int_scale(&spec)? {
+            Some(scale) => WavSamples::Int(reader.into_samples(), scale),
+            None => WavSamples::Float(reader.into_samples()),
+        };
+
+        let (resampler, sample_rate) = match options.sample_rate {
+            Some(target) if target != spec.sample_rate => {
+                (Some(Resampler::new(spec.sample_rate, target)?), target)
+            }
+            _ => (None, spec.sample_rate),
+        };
+
+        Ok(Self {
+            samples,
+            channels: spec.channels,
+            selection: options.channels,
+            resampler,
+            sample_rate,
+            chunk_size,
+            pending: Vec::with_capacity(chunk_size * 2),
+            finished: false,
+        })
+    }
+
+    /// Sample rate of the returned chunks
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    fn fill(&mut self) -> Result<()> {
+        let wanted = self.chunk_size * self.channels as usize;
+        let mut interleaved = Vec::with_capacity(wanted);
+        self.samples.read(wanted, &mut interleaved)?;
+        let eof = interleaved.len() < wanted;
+
+        let samples = select_channels(interleaved, self.channels, self.selection)?;
+        match self.resampler.as_mut() {
+            Some(resampler) => self.pending.extend(resampler.resample_chunk(&samples, eof)),
+            None => self.pending.extend(samples),
+        }
+        self.finished = eof;
+        Ok(())
+    }
+}
+
+impl Iterator for ChunkReader {
+    type Item = Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.len() < self.chunk_size && !self.finished {
+            if let Err(err) = self.fill() {
+                self.finished = true;
+                self.pending.clear();
+                return Some(Err(err));
+            }
+        }
+        if self.pending.is_empty() {
+            return None;
+        }
+        let n = self.chunk_size.min(self.pending.len());
+        Some(Ok(self.pending.drain(..n).collect()))
+    }
+}
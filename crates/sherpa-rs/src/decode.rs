@@ -0,0 +1,114 @@
+use std::{fs::File, io::Cursor, path::Path};
+
+use eyre::{bail, Result};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS},
+    errors::Error,
+    formats::FormatOptions,
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+use crate::{convert_audio, AudioError, ReadAudioOptions};
+
+/// Decode a compressed audio file (FLAC, Ogg/Vorbis, MP3, AAC/M4A, WAV).
+///
+/// Opus is not supported since there's no pure Rust decoder for it yet.
+pub fn decode_audio_file(path: &str, options: ReadAudioOptions) -> Result<(Vec<f32>, u32)> {
+    let mut hint = Hint::new();
+    if let Some(extension) = Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(extension);
+    }
+    let file = File::open(path)?;
+    decode(Box::new(file), hint, options)
+}
+
+/// Decode audio from memory, eg. an uploaded file. `extension` helps to guess the container.
+pub fn decode_audio_bytes(
+    bytes: Vec<u8>,
+    extension: Option<&str>,
+    options: ReadAudioOptions,
+) -> Result<(Vec<f32>, u32)> {
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    decode(Box::new(Cursor::new(bytes)), hint, options)
+}
+
+fn decode(
+    source: Box<dyn MediaSource>,
+    hint: Hint,
+    options: ReadAudioOptions,
+) -> Result<(Vec<f32>, u32)> {
+    let stream = MediaSourceStream::new(source, Default::default());
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+
+    let Some(track) = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    else {
+        bail!("No audio track found");
+    };
+    let track_id = track.id;
+    let codec = track.codec_params.codec;
+
+    let codecs = symphonia::default::get_codecs();
+    if codecs.get_codec(codec).is_none() {
+        let codec = if codec == CODEC_TYPE_OPUS {
+            "opus".into()
+        } else {
+            format!("{:?}", codec)
+        };
+        return Err(AudioError::UnsupportedCodec { codec }.into());
+    }
+    let mut decoder = codecs.make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track
+        .codec_params
+        .channels
+        .map(|channels| channels.count())
+        .unwrap_or(0);
+    let mut interleaved = Vec::new();
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = spec.rate;
+                channels = spec.channels.count();
+
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                interleaved.extend_from_slice(buffer.samples());
+            }
+            // Corrupted packets are skipped, like most players do
+            Err(Error::DecodeError(err)) => tracing::warn!("skipping undecodable packet: {}", err),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    if sample_rate == 0 || channels == 0 {
+        bail!("Could not determine sample rate or channel count");
+    }
+    convert_audio(interleaved, channels as u16, sample_rate, &options)
+}
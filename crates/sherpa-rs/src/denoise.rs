@@ -0,0 +1,104 @@
+use eyre::{bail, Result};
+
+use crate::{get_default_provider, resampler, utils::cstring_from_str};
+
+#[derive(Debug, Default, Clone)]
+pub struct DenoiserConfig {
+    /// GTCRN model, eg. gtcrn_simple.onnx
+    pub model: String,
+    pub debug: bool,
+    pub num_threads: Option<i32>,
+    pub provider: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DenoisedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// Offline speech enhancement.
+///
+/// Denoise whole recordings or chunks of a few seconds, very short chunks produce
+/// artifacts at their edges.
+pub struct Denoiser {
+    denoiser: *const sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiser,
+}
+
+impl Denoiser {
+    pub fn new(config: DenoiserConfig) -> Result<Self> {
+        let model = cstring_from_str(&config.model);
+        let provider = cstring_from_str(&config.provider.unwrap_or(get_default_provider()));
+
+        let sherpa_config = sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiserConfig {
+            model: sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiserModelConfig {
+                gtcrn: sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiserGtcrnModelConfig {
+                    model: model.as_ptr(),
+                },
+                num_threads: config.num_threads.unwrap_or(1),
+                debug: config.debug.into(),
+                provider: provider.as_ptr(),
+            },
+        };
+        let denoiser =
+            unsafe { sherpa_rs_sys::SherpaOnnxCreateOfflineSpeechDenoiser(&sherpa_config) };
+
+        if denoiser.is_null() {
+            bail!("Failed to create speech denoiser");
+        }
+        Ok(Self { denoiser })
+    }
+
+    /// Sample rate of the model and of the denoised audio
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiserGetSampleRate(self.denoiser) as u32 }
+    }
+
+    /// Denoise mono samples of any sample rate. The result is at [`Denoiser::sample_rate`].
+    pub fn denoise(&mut self, samples: &[f32], sample_rate: u32) -> Result<DenoisedAudio> {
+        unsafe {
+            let audio = sherpa_rs_sys::SherpaOnnxOfflineSpeechDenoiserRun(
+                self.denoiser,
+                samples.as_ptr(),
+                samples.len() as i32,
+                sample_rate as i32,
+            );
+            if audio.is_null() {
+                bail!("Failed to denoise audio");
+            }
+            let denoised = DenoisedAudio {
+                samples: if (*audio).samples.is_null() {
+                    Vec::new()
+                } else {
+                    std::slice::from_raw_parts((*audio).samples, (*audio).n as usize).to_vec()
+                },
+                sample_rate: (*audio).sample_rate as u32,
+            };
+            sherpa_rs_sys::SherpaOnnxDestroyDenoisedAudio(audio);
+            Ok(denoised)
+        }
+    }
+
+    /// Denoise and return samples at `target_sample_rate`, eg. 16kHz before `SileroVad`
+    /// or a recognizer.
+    pub fn denoise_to(
+        &mut self,
+        samples: &[f32],
+        sample_rate: u32,
+        target_sample_rate: u32,
+    ) -> Result<Vec<f32>> {
+        let audio = self.denoise(samples, sample_rate)?;
+        resampler::resample(&audio.samples, audio.sample_rate, target_sample_rate)
+    }
+}
+
+unsafe impl Send for Denoiser {}
+unsafe impl Sync for Denoiser {}
+
+impl Drop for Denoiser {
+    fn drop(&mut self) {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxDestroyOfflineSpeechDenoiser(self.denoiser);
+        }
+    }
+}
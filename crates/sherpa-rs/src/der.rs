@@ -0,0 +1,411 @@
+use crate::diarize::Segment;
+
+#[derive(Debug, Clone)]
+pub struct ScoreOptions {
+    /// Seconds around each reference boundary that aren't scored
+    pub collar: f32,
+    /// Don't score regions where several reference speakers talk
+    pub skip_overlap: bool,
+}
+
+impl Default for ScoreOptions {
+    fn default() -> Self {
+        Self {
+            collar: 0.25,
+            skip_overlap: false,
+        }
+    }
+}
+
+/// Durations are in seconds of scored reference speech.
+#[derive(Debug, Clone, Default)]
+pub struct DiarizationScore {
+    /// Diarization error rate, (missed + false alarm + confusion) / total
+    pub der: f32,
+    /// Jaccard error rate as in dscore (DIHARD), see [`score`]
+    pub jer: f32,
+    pub missed: f32,
+    pub false_alarm: f32,
+    pub confusion: f32,
+    pub total: f32,
+    /// Pairs of (reference, hypothesis) speakers matched for DER
+    pub mapping: Vec<(i32, i32)>,
+}
+
+/// Region between two consecutive boundaries with the active speakers (as indices)
+struct Region {
+    duration: f64,
+    reference: Vec<usize>,
+    hypothesis: Vec<usize>,
+}
+
+fn speakers(segments: &[Segment]) -> Vec<i32> {
+    let mut speakers: Vec<i32> = segments.iter().map(|s| s.speaker).collect();
+    speakers.sort_unstable();
+    speakers.dedup();
+    speakers
+}
+
+fn active(segments: &[Segment], speakers: &[i32], time: f64) -> Vec<usize> {
+    let mut active: Vec<usize> = segments
+        .iter()
+        .filter(|s| s.start as f64 <= time && time < s.end as f64)
+        .map(|s| speakers.binary_search(&s.speaker).unwrap())
+        .collect();
+    active.sort_unstable();
+    active.dedup();
+    active
+}
+
+fn regions(
+    reference: &[Segment],
+    hypothesis: &[Segment],
+    reference_speakers: &[i32],
+    hypothesis_speakers: &[i32],
+    options: &ScoreOptions,
+) -> Vec<Region> {
+    let collar = options.collar.max(0.0) as f64;
+    let mut reference_boundaries: Vec<f64> = reference
+        .iter()
+        .flat_map(|s| [s.start as f64, s.end as f64])
+        .collect();
+    reference_boundaries.sort_by(f64::total_cmp);
+
+    let mut boundaries: Vec<f64> = hypothesis
+        .iter()
+        .flat_map(|s| [s.start as f64, s.end as f64])
+        .chain(
+            reference_boundaries
+                .iter()
+                .flat_map(|&b| [b - collar, b, b + collar]),
+        )
+        .collect();
+    boundaries.sort_by(f64::total_cmp);
+    boundaries.dedup();
+
+    let in_collar = |time: f64| {
+        let i = reference_boundaries.partition_point(|&b| b < time);
+        let near = |j: usize| {
+            reference_boundaries
+                .get(j)
+                .is_some_and(|&b| (b - time).abs() < collar)
+        };
+        near(i) || (i > 0 && near(i - 1))
+    };
+
+    boundaries
+        .windows(2)
+        .filter_map(|window| {
+            let middle = (window[0] + window[1]) / 2.0;
+            if collar > 0.0 && in_collar(middle) {
+                return None;
+            }
+            let reference = active(reference, reference_speakers, middle);
+            if options.skip_overlap && reference.len() > 1 {
+                return None;
+            }
+            Some(Region {
+                duration: window[1] - window[0],
+                reference,
+                hypothesis: active(hypothesis, hypothesis_speakers, middle),
+            })
+        })
+        .collect()
+}
+
+/// Score `hypothesis` against `reference` with the speaker mapping that minimizes the error.
+///
+/// JER follows dscore: the mean over reference speakers of `1 - IoU` with the hypothesis
+/// speaker from the mapping that minimizes it, 1 for unmatched reference speakers.
+/// Extra hypothesis speakers only count through DER's false alarm.
+pub fn score(
+    reference: &[Segment],
+    hypothesis: &[Segment],
+    options: &ScoreOptions,
+) -> DiarizationScore {
+    let reference_speakers = speakers(reference);
+    let hypothesis_speakers = speakers(hypothesis);
+    let regions = regions(
+        reference,
+        hypothesis,
+        &reference_speakers,
+        &hypothesis_speakers,
+        options,
+    );
+
+    // Time each pair of speakers talks together, and each speaker talks
+    let mut overlap = vec![vec![0.0; hypothesis_speakers.len()]; reference_speakers.len()];
+    let mut reference_time = vec![0.0; reference_speakers.len()];
+    let mut hypothesis_time = vec![0.0; hypothesis_speakers.len()];
+    for region in &regions {
+        for &r in &region.reference {
+            reference_time[r] += region.duration;
+            for &h in &region.hypothesis {
+                overlap[r][h] += region.duration;
+            }
+        }
+        for &h in &region.hypothesis {
+            hypothesis_time[h] += region.duration;
+        }
+    }
+
+    let cost: Vec<Vec<f64>> = overlap
+        .iter()
+        .map(|row| row.iter().map(|&o| -o).collect())
+        .collect();
+    // Padding can pair speakers that never talk together, they aren't a match
+    let mapping: Vec<Option<usize>> = hungarian(&cost)
+        .into_iter()
+        .enumerate()
+        .map(|(r, h)| h.filter(|&h| overlap[r][h] > 0.0))
+        .collect();
+
+    let (mut total, mut missed, mut false_alarm, mut confusion) = (0.0, 0.0, 0.0, 0.0);
+    for region in &regions {
+        let (n_reference, n_hypothesis) = (region.reference.len(), region.hypothesis.len());
+        let correct = region
+            .reference
+            .iter()
+            .filter(|&&r| mapping[r].is_some_and(|h| region.hypothesis.contains(&h)))
+            .count();
+        total += region.duration * n_reference as f64;
+        missed += region.duration * n_reference.saturating_sub(n_hypothesis) as f64;
+        false_alarm += region.duration * n_hypothesis.saturating_sub(n_reference) as f64;
+        confusion += region.duration * (n_reference.min(n_hypothesis) - correct) as f64;
+    }
+    let der = if total > 0.0 {
+        (missed + false_alarm + confusion) / total
+    } else {
+        0.0
+    };
+
+    DiarizationScore {
+        der: der as f32,
+        jer: jaccard_error_rate(&overlap, &reference_time, &hypothesis_time) as f32,
+        missed: missed as f32,
+        false_alarm: false_alarm as f32,
+        confusion: confusion as f32,
+        total: total as f32,
+        mapping: mapping
+            .iter()
+            .enumerate()
+            .filter_map(|(r, h)| Some((reference_speakers[r], hypothesis_speakers[(*h)?])))
+            .collect(),
+    }
+}
+
+/// Mean of 1 - IoU over reference speakers with their optimal hypothesis speaker.
+/// Without reference speakers it's 0, or 1 if the hypothesis has speakers.
+fn jaccard_error_rate(overlap: &[Vec<f64>], reference: &[f64], hypothesis: &[f64]) -> f64 {
+    let union = |r: usize, h: usize| reference[r] + hypothesis[h] - overlap[r][h];
+    let cost: Vec<Vec<f64>> = (0..reference.len())
+        .map(|r| {
+            (0..hypothesis.len())
+                .map(|h| {
+                    let union = union(r, h);
+                    if union > 0.0 {
+                        1.0 - overlap[r][h] / union
+                    } else {
+                        1.0
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let mapping = hungarian(&cost);
+
+    if reference.is_empty() {
+        return if hypothesis.is_empty() { 0.0 } else { 1.0 };
+    }
+    let errors: f64 = mapping
+        .iter()
+        .enumerate()
+        .map(|(r, h)| h.map_or(1.0, |h| cost[r][h]))
+        .sum();
+    errors / reference.len() as f64
+}
+
+/// Minimum cost assignment of rows to columns. Returns the column of each row,
+/// `None` for rows left over when there are more rows than columns.
+pub(crate) fn hungarian(cost: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let rows = cost.len();
+    let cols = cost.first().map_or(0, |row| row.len());
+    if rows == 0 || cols == 0 {
+        return vec![None; rows];
+    }
+    // Square matrix padded with zeros
+    let n = rows.max(cols);
+    let at = |i: usize, j: usize| {
+        if i < rows && j < cols {
+            cost[i][j]
+        } else {
+            0.0
+        }
+    };
+
+    // Potentials based O(n^3) algorithm, 1-indexed with 0 as a sentinel
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; n + 1];
+    let mut matched_row = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+    for i in 1..=n {
+        matched_row[0] = i;
+        let mut j0 = 0;
+        let mut min_value = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = matched_row[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let current = at(i0 - 1, j - 1) - u[i0] - v[j];
+                if current < min_value[j] {
+                    min_value[j] = current;
+                    way[j] = j0;
+                }
+                if min_value[j] < delta {
+                    delta = min_value[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[matched_row[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_value[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if matched_row[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            matched_row[j0] = matched_row[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for (j, &i) in matched_row.iter().enumerate().skip(1) {
+        if (1..=rows).contains(&i) && j <= cols {
+            assignment[i - 1] = Some(j - 1);
+        }
+    }
+    assignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f32, end: f32, speaker: i32) -> Segment {
+        Segment {
+            start,
+            end,
+            speaker,
+        }
+    }
+
+    fn no_collar() -> ScoreOptions {
+        ScoreOptions {
+            collar: 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn hungarian_more_columns_than_rows() {
+        let cost = vec![vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 6.0]];
+        assert_eq!(hungarian(&cost), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn hungarian_more_rows_than_columns() {
+        let cost = vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]];
+        assert_eq!(hungarian(&cost), vec![Some(1), Some(0), None]);
+    }
+
+    #[test]
+    fn identical_is_perfect() {
+        let reference = [segment(0.0, 5.0, 0), segment(5.0, 12.0, 1)];
+        let score = score(&reference, &reference, &ScoreOptions::default());
+        assert_close(score.der, 0.0);
+        assert_close(score.jer, 0.0);
+    }
+
+    #[test]
+    fn permuted_labels_are_perfect() {
+        let reference = [segment(0.0, 5.0, 0), segment(5.0, 12.0, 1)];
+        let hypothesis = [segment(0.0, 5.0, 7), segment(5.0, 12.0, 3)];
+        let score = score(&reference, &hypothesis, &no_collar());
+        assert_close(score.der, 0.0);
+        assert_close(score.jer, 0.0);
+        assert_eq!(score.mapping, vec![(0, 7), (1, 3)]);
+    }
+
+    #[test]
+    fn empty_hypothesis_is_missed() {
+        let reference = [segment(0.0, 10.0, 0)];
+        let score = score(&reference, &[], &no_collar());
+        assert_close(score.missed, 10.0);
+        assert_close(score.total, 10.0);
+        assert_close(score.der, 1.0);
+        assert_close(score.jer, 1.0);
+    }
+
+    #[test]
+    fn extra_speech_is_false_alarm() {
+        let reference = [segment(0.0, 10.0, 0)];
+        let hypothesis = [segment(0.0, 10.0, 0), segment(20.0, 25.0, 1)];
+        let score = score(&reference, &hypothesis, &no_collar());
+        assert_close(score.false_alarm, 5.0);
+        assert_close(score.missed, 0.0);
+        assert_close(score.confusion, 0.0);
+        assert_close(score.der, 0.5);
+        // The extra hypothesis speaker isn't a reference speaker
+        assert_close(score.jer, 0.0);
+    }
+
+    #[test]
+    fn wrong_speaker_is_confusion() {
+        let reference = [segment(0.0, 10.0, 0), segment(10.0, 20.0, 1)];
+        let hypothesis = [segment(0.0, 20.0, 0)];
+        let score = score(&reference, &hypothesis, &no_collar());
+        assert_close(score.confusion, 10.0);
+        assert_close(score.missed, 0.0);
+        assert_close(score.false_alarm, 0.0);
+        assert_close(score.der, 0.5);
+        // Speaker 0 has IoU 0.5, speaker 1 is unmatched
+        assert_close(score.jer, 0.75);
+    }
+
+    #[test]
+    fn collar_excludes_boundaries() {
+        let reference = [segment(0.0, 10.0, 0)];
+        let hypothesis = [segment(0.2, 9.9, 0)];
+        assert_close(score(&reference, &hypothesis, &no_collar()).missed, 0.3);
+
+        let score = score(&reference, &hypothesis, &ScoreOptions::default());
+        assert_close(score.der, 0.0);
+        assert_close(score.total, 9.5);
+    }
+}
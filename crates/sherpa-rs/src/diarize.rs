@@ -1,6 +1,13 @@
 use crate::{get_default_provider, utils::cstring_from_str};
 use eyre::{bail, Result};
-use std::{path::Path, ptr::null_mut};
+use std::{
+    mem,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 #[derive(Debug)]
 pub struct Diarize {
@@ -14,14 +21,56 @@ pub struct Segment {
     pub speaker: i32,
 }
 
-type ProgressCallback = Box<dyn (Fn(i32, i32) -> i32) + Send + 'static>;
+/// Receives the processed and total number of chunks, returns non-zero to stop.
+pub type ProgressCallback = Box<dyn (Fn(i32, i32) -> i32) + Send + 'static>;
+
+/// Cancels a running diarization from another thread. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Errors returned (wrapped in [`eyre::Report`]) by [`Diarize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiarizeError {
+    Cancelled,
+}
+
+impl std::fmt::Display for DiarizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiarizeError::Cancelled => write!(f, "diarization was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for DiarizeError {}
 
 #[derive(Debug, Clone)]
 pub struct DiarizeConfig {
+    /// Number of speakers, `None` clusters by `threshold` instead
     pub num_clusters: Option<i32>,
+    /// Clustering threshold used when `num_clusters` is `None`. sherpa-onnx replaces
+    /// `0.0` with the default `0.5`.
     pub threshold: Option<f32>,
     pub min_duration_on: Option<f32>,
     pub min_duration_off: Option<f32>,
+    pub segmentation_num_threads: Option<i32>,
+    pub embedding_num_threads: Option<i32>,
     pub provider: Option<String>,
     pub debug: bool,
 }
@@ -33,6 +82,8 @@ impl Default for DiarizeConfig {
             threshold: Some(0.5),
             min_duration_on: Some(0.0),
             min_duration_off: Some(0.0),
+            segmentation_num_threads: Some(1),
+            embedding_num_threads: Some(1),
             provider: None,
             debug: false,
         }
@@ -54,7 +105,7 @@ impl Diarize {
         let segmentation_model = segmentation_model.as_ref().to_str().unwrap();
 
         let clustering_config = sherpa_rs_sys::SherpaOnnxFastClusteringConfig {
-            num_clusters: config.num_clusters.unwrap_or(4),
+            num_clusters: config.num_clusters.unwrap_or(-1),
             threshold: config.threshold.unwrap_or(0.5),
         };
 
@@ -65,7 +116,7 @@ impl Diarize {
         let config = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationConfig {
             embedding: sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingExtractorConfig {
                 model: embedding_model.as_ptr(),
-                num_threads: 1,
+                num_threads: config.embedding_num_threads.unwrap_or(1),
                 debug,
                 provider: provider.as_ptr(),
             },
@@ -76,7 +127,7 @@ impl Diarize {
                 pyannote: sherpa_rs_sys::SherpaOnnxOfflineSpeakerSegmentationPyannoteModelConfig {
                     model: segmentation_model.as_ptr(),
                 },
-                num_threads: 1,
+                num_threads: config.segmentation_num_threads.unwrap_or(1),
                 debug,
                 provider: provider.as_ptr(),
             },
@@ -90,71 +141,131 @@ impl Diarize {
         Ok(Self { sd })
     }
 
+    /// Sample rate the samples passed to `compute` must have
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationGetSampleRate(self.sd) as u32 }
+    }
+
+    /// Change the clustering without reloading the models, eg. for a number of speakers
+    /// slider. As in [`DiarizeConfig`], with `num_clusters` set to `None` speakers are
+    /// clustered by `threshold`, and sherpa-onnx replaces a `threshold` of `0.0` with `0.5`.
+    ///
+    /// Takes effect on the next `compute`, which still runs segmentation and embedding.
+    pub fn set_clustering(&mut self, num_clusters: Option<i32>, threshold: f32) {
+        unsafe {
+            // Only the clustering config is read by sherpa-onnx
+            let mut config: sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationConfig =
+                mem::zeroed();
+            config.clustering = sherpa_rs_sys::SherpaOnnxFastClusteringConfig {
+                num_clusters: num_clusters.unwrap_or(-1),
+                threshold,
+            };
+            sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationSetConfig(self.sd, &config);
+        }
+    }
+
+    /// `progress_callback` receives the processed and total number of chunks.
+    /// Returning non-zero stops processing and `compute` returns [`DiarizeError::Cancelled`].
     pub fn compute(
         &mut self,
-        mut samples: Vec<f32>,
+        samples: Vec<f32>,
         progress_callback: Option<ProgressCallback>,
     ) -> Result<Vec<Segment>> {
-        let samples_ptr = samples.as_mut_ptr();
-        let mut segments = Vec::new();
-        unsafe {
-            let mut callback_box =
-                progress_callback.map(|cb| Box::new(cb) as Box<ProgressCallback>);
-            let callback_ptr = callback_box
-                .as_mut()
-                .map(|b| b.as_mut() as *mut ProgressCallback as *mut std::ffi::c_void)
-                .unwrap_or(null_mut());
+        match progress_callback {
+            Some(callback) => {
+                self.compute_with_callback(&samples, None, |n, total| callback(n, total) == 0)
+            }
+            None => self.compute_with_callback(&samples, None, |_, _| true),
+        }
+    }
 
+    /// Like `compute` but the callback can borrow local state. It receives the processed
+    /// and total number of chunks and returns `false` to stop.
+    ///
+    /// When stopped by the callback or `cancel`, returns [`DiarizeError::Cancelled`].
+    pub fn compute_with_callback<F: FnMut(i32, i32) -> bool>(
+        &mut self,
+        samples: &[f32],
+        cancel: Option<&CancellationToken>,
+        mut callback: F,
+    ) -> Result<Vec<Segment>> {
+        if cancel.is_some_and(|cancel| cancel.is_cancelled()) {
+            return Err(DiarizeError::Cancelled.into());
+        }
+        let mut state = CallbackState {
+            callback: &mut callback,
+            cancel,
+            stopped: false,
+        };
+
+        unsafe {
             let result = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationProcessWithCallback(
                 self.sd,
-                samples_ptr,
+                samples.as_ptr(),
                 samples.len() as i32,
-                if callback_box.is_some() {
-                    Some(progress_callback_wrapper)
-                } else {
-                    None
-                },
-                callback_ptr,
+                Some(progress_callback_wrapper),
+                &mut state as *mut CallbackState as *mut std::ffi::c_void,
             );
+            if state.stopped {
+                if !result.is_null() {
+                    sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroyResult(result);
+                }
+                return Err(DiarizeError::Cancelled.into());
+            }
+            if result.is_null() {
+                bail!("Failed to diarize audio");
+            }
 
             let num_segments =
                 sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationResultGetNumSegments(result);
             let segments_ptr: *const sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationSegment =
                 sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationResultSortByStartTime(result);
 
+            let mut segments = Vec::new();
             if !segments_ptr.is_null() && num_segments > 0 {
                 let segments_result: &[
                     sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationSegment
                 ] = std::slice::from_raw_parts(segments_ptr, num_segments as usize);
 
                 for segment in segments_result {
-                    // Use segment here
-
                     segments.push(Segment {
                         start: segment.start,
                         end: segment.end,
                         speaker: segment.speaker,
                     });
                 }
-            } else {
-                bail!("No segments found or invalid pointer.");
             }
 
-            sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroySegment(segments_ptr);
+            if !segments_ptr.is_null() {
+                sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroySegment(segments_ptr);
+            }
             sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroyResult(result);
 
+            if segments.is_empty() {
+                bail!("No segments found or invalid pointer.");
+            }
             Ok(segments)
         }
     }
 }
 
+struct CallbackState<'a> {
+    callback: &'a mut dyn FnMut(i32, i32) -> bool,
+    cancel: Option<&'a CancellationToken>,
+    stopped: bool,
+}
+
 unsafe extern "C" fn progress_callback_wrapper(
     num_processed_chunk: i32,
     num_total_chunks: i32,
     arg: *mut std::ffi::c_void,
 ) -> i32 {
-    let callback = &mut *(arg as *mut ProgressCallback);
-    callback(num_processed_chunk, num_total_chunks)
+    let state = &mut *(arg as *mut CallbackState);
+    let cancelled = state.cancel.is_some_and(|cancel| cancel.is_cancelled());
+    if cancelled || !(state.callback)(num_processed_chunk, num_total_chunks) {
+        state.stopped = true;
+    }
+    state.stopped as i32
 }
 
 unsafe impl Send for Diarize {}
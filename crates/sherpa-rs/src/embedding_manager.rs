@@ -1,9 +1,12 @@
 use crate::utils::{cstr_to_string, cstring_from_str};
 use eyre::{bail, Result};
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct EmbeddingManager {
     pub(crate) manager: *const sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManager,
+    dimension: i32,
+    /// Embeddings of each speaker in enrollment order, the native manager only keeps their mean
+    speakers: Vec<(String, Vec<Vec<f32>>)>,
 }
 
 #[derive(Debug, Clone)]
@@ -16,10 +19,18 @@ impl EmbeddingManager {
     pub fn new(dimension: i32) -> Self {
         unsafe {
             let manager = sherpa_rs_sys::SherpaOnnxCreateSpeakerEmbeddingManager(dimension);
-            Self { manager }
+            Self {
+                manager,
+                dimension,
+                speakers: Vec::new(),
+            }
         }
     }
 
+    pub fn dimension(&self) -> i32 {
+        self.dimension
+    }
+
     pub fn search(&mut self, embedding: &[f32], threshold: f32) -> Option<String> {
         unsafe {
             let name = sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerSearch(
@@ -66,7 +77,9 @@ impl EmbeddingManager {
         }
     }
 
+    /// Fails if `name` is already registered or the dimension doesn't match.
     pub fn add(&mut self, name: String, embedding: &mut [f32]) -> Result<()> {
+        self.check_dimension(&name, embedding)?;
         let name_c = cstring_from_str(&name.clone());
         unsafe {
             let status = sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerAdd(
@@ -75,11 +88,120 @@ impl EmbeddingManager {
                 embedding.as_mut_ptr(),
             );
 
-            if status.is_negative() {
+            // 1 on success, 0 when the name exists (it's never negative)
+            if status == 0 {
                 bail!("Failed to register {}", name);
             }
-            Ok(())
         }
+        self.speakers.push((name, vec![embedding.to_vec()]));
+        Ok(())
+    }
+
+    /// Register a speaker with several embeddings, eg. from different recordings.
+    /// Matching uses their mean.
+    pub fn add_list(&mut self, name: String, embeddings: &[Vec<f32>]) -> Result<()> {
+        if embeddings.is_empty() {
+            bail!("No embeddings to register for {}", name);
+        }
+        for embedding in embeddings {
+            self.check_dimension(&name, embedding)?;
+        }
+        let flattened: Vec<f32> = embeddings.concat();
+        let name_c = cstring_from_str(&name);
+        unsafe {
+            let status = sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerAddListFlattened(
+                self.manager,
+                name_c.as_ptr(),
+                flattened.as_ptr(),
+                embeddings.len() as i32,
+            );
+            if status == 0 {
+                bail!("Failed to register {}", name);
+            }
+        }
+        self.speakers.push((name, embeddings.to_vec()));
+        Ok(())
+    }
+
+    /// Speakers with their embeddings in enrollment order
+    pub fn speakers(&self) -> impl ExactSizeIterator<Item = (&str, &[Vec<f32>])> {
+        self.speakers
+            .iter()
+            .map(|(name, embeddings)| (name.as_str(), embeddings.as_slice()))
+    }
+
+    fn check_dimension(&self, name: &str, embedding: &[f32]) -> Result<()> {
+        if embedding.len() != self.dimension as usize {
+            bail!(
+                "Embedding dimension {} of {} doesn't match {}",
+                embedding.len(),
+                name,
+                self.dimension
+            );
+        }
+        Ok(())
+    }
+
+    /// Embeddings registered for `name`
+    pub fn embeddings(&self, name: &str) -> Option<&[Vec<f32>]> {
+        self.speakers
+            .iter()
+            .find(|(speaker, _)| speaker == name)
+            .map(|(_, embeddings)| embeddings.as_slice())
+    }
+
+    /// Returns `false` if `name` isn't registered.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.speakers.retain(|(speaker, _)| speaker != name);
+        let name = cstring_from_str(name);
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerRemove(self.manager, name.as_ptr()) != 0
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        let name = cstring_from_str(name);
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerContains(self.manager, name.as_ptr())
+                != 0
+        }
+    }
+
+    pub fn num_speakers(&self) -> i32 {
+        unsafe { sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerNumSpeakers(self.manager) }
+    }
+
+    pub fn all_speakers(&self) -> Vec<String> {
+        unsafe {
+            let num_speakers = self.num_speakers();
+            if num_speakers <= 0 {
+                return Vec::new();
+            }
+            let names_ptr =
+                sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerGetAllSpeakers(self.manager);
+            if names_ptr.is_null() {
+                return Vec::new();
+            }
+            let names = std::slice::from_raw_parts(names_ptr, num_speakers as usize)
+                .iter()
+                .map(|&name| cstr_to_string(name as _))
+                .collect();
+            sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingManagerFreeAllSpeakers(names_ptr);
+            names
+        }
+    }
+}
+
+impl Clone for EmbeddingManager {
+    /// Creates a new native manager with the same speakers. Copying the handle would
+    /// destroy it twice on drop.
+    fn clone(&self) -> Self {
+        let mut manager = Self::new(self.dimension);
+        for (name, embeddings) in &self.speakers {
+            // Same dimension and unique names, registering can't fail
+            let _ = manager.add_list(name.clone(), embeddings);
+        }
+        manager
     }
 }
 
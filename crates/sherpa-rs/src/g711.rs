@@ -0,0 +1,142 @@
+use eyre::Result;
+
+use crate::resampler;
+
+/// Sample rate of G.711 telephony audio
+pub const SAMPLE_RATE: u32 = 8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum G711Law {
+    /// PCMU, used in North America and Japan
+    MuLaw,
+    /// PCMA, used in Europe and most other countries
+    ALaw,
+}
+
+const QUANT_MASK: i32 = 0x0F;
+const SEG_SHIFT: i32 = 4;
+const SEG_MASK: i32 = 0x70;
+const SIGN_BIT: i32 = 0x80;
+const BIAS: i32 = 0x84;
+const CLIP: i32 = 8159;
+
+const SEG_ALAW_END: [i32; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+const SEG_ULAW_END: [i32; 8] = [0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF, 0x1FFF];
+
+fn segment(value: i32, table: &[i32; 8]) -> i32 {
+    table
+        .iter()
+        .position(|&end| value <= end)
+        .unwrap_or(table.len()) as i32
+}
+
+fn linear_to_ulaw(pcm: i16) -> u8 {
+    let mut pcm = (pcm as i32) >> 2;
+    let mask = if pcm < 0 {
+        pcm = -pcm;
+        0x7F
+    } else {
+        0xFF
+    };
+    pcm = pcm.min(CLIP) + (BIAS >> 2);
+
+    let seg = segment(pcm, &SEG_ULAW_END);
+    if seg >= 8 {
+        return (0x7F ^ mask) as u8;
+    }
+    let value = (seg << 4) | ((pcm >> (seg + 1)) & QUANT_MASK);
+    (value ^ mask) as u8
+}
+
+fn ulaw_to_linear(value: u8) -> i16 {
+    let value = !value as i32;
+    let mut t = ((value & QUANT_MASK) << 3) + BIAS;
+    t <<= (value & SEG_MASK) >> SEG_SHIFT;
+    if value & SIGN_BIT != 0 {
+        (BIAS - t) as i16
+    } else {
+        (t - BIAS) as i16
+    }
+}
+
+fn linear_to_alaw(pcm: i16) -> u8 {
+    let mut pcm = (pcm as i32) >> 3;
+    let mask = if pcm >= 0 {
+        0xD5
+    } else {
+        pcm = -pcm - 1;
+        0x55
+    };
+
+    let seg = segment(pcm, &SEG_ALAW_END);
+    if seg >= 8 {
+        return (0x7F ^ mask) as u8;
+    }
+    let mut value = seg << SEG_SHIFT;
+    if seg < 2 {
+        value |= (pcm >> 1) & QUANT_MASK;
+    } else {
+        value |= (pcm >> seg) & QUANT_MASK;
+    }
+    (value ^ mask) as u8
+}
+
+fn alaw_to_linear(value: u8) -> i16 {
+    let value = (value ^ 0x55) as i32;
+    let mut t = (value & QUANT_MASK) << 4;
+    let seg = (value & SEG_MASK) >> SEG_SHIFT;
+    match seg {
+        0 => t += 8,
+        1 => t += 0x108,
+        _ => {
+            t += 0x108;
+            t <<= seg - 1;
+        }
+    }
+    if value & SIGN_BIT != 0 {
+        t as i16
+    } else {
+        -t as i16
+    }
+}
+
+/// Encode samples in range [-1, 1] to G.711 bytes. Samples must already be at 8kHz.
+pub fn encode(samples: &[f32], law: G711Law) -> Vec<u8> {
+    samples
+        .iter()
+        .map(|&sample| {
+            let pcm = (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+            match law {
+                G711Law::MuLaw => linear_to_ulaw(pcm),
+                G711Law::ALaw => linear_to_alaw(pcm),
+            }
+        })
+        .collect()
+}
+
+/// Decode G.711 bytes to 8kHz samples in range [-1, 1].
+pub fn decode(bytes: &[u8], law: G711Law) -> Vec<f32> {
+    bytes
+        .iter()
+        .map(|&byte| {
+            let pcm = match law {
+                G711Law::MuLaw => ulaw_to_linear(byte),
+                G711Law::ALaw => alaw_to_linear(byte),
+            };
+            pcm as f32 / 32768.0
+        })
+        .collect()
+}
+
+/// Resample audio at `sample_rate` to 8kHz and encode it, eg. TTS output going to RTP.
+pub fn encode_from(samples: &[f32], sample_rate: u32, law: G711Law) -> Result<Vec<u8>> {
+    let samples = resampler::resample(samples, sample_rate, SAMPLE_RATE)?;
+    Ok(encode(&samples, law))
+}
+
+/// Decode G.711 bytes and resample to `sample_rate`, eg. 16kHz for the recognizers.
+///
+/// For a live stream use [`decode`] with a [`resampler::Resampler`] to keep state between frames.
+pub fn decode_to(bytes: &[u8], law: G711Law, sample_rate: u32) -> Result<Vec<f32>> {
+    resampler::resample(&decode(bytes, law), SAMPLE_RATE, sample_rate)
+}
@@ -1,17 +1,28 @@
 pub mod audio_tag;
+pub mod audio_writer;
+pub mod chunk_reader;
+pub mod denoise;
+pub mod der;
 pub mod diarize;
 pub mod dolphin;
 pub mod embedding_manager;
+pub mod g711;
 pub mod keyword_spot;
 pub mod language_id;
 pub mod moonshine;
+pub mod named_diarize;
+pub mod online_diarize;
 pub mod paraformer;
 pub mod punctuate;
+pub mod resampler;
+pub mod rttm;
 pub mod sense_voice;
 pub mod silero_vad;
+pub mod speaker_db;
 pub mod speaker_id;
 pub mod ten_vad;
 pub mod transducer;
+pub mod vad;
 pub mod whisper;
 pub mod zipformer;
 
@@ -20,12 +31,16 @@ mod utils;
 #[cfg(feature = "tts")]
 pub mod tts;
 
+#[cfg(feature = "decode")]
+pub mod decode;
+
 use std::ffi::CStr;
 
 #[cfg(feature = "sys")]
 pub use sherpa_rs_sys;
 
-use eyre::{bail, Result};
+use audio_writer::{AudioWriter, WriteAudioOptions};
+use eyre::Result;
 use utils::cstr_to_string;
 
 pub fn get_default_provider() -> String {
@@ -43,44 +58,194 @@ pub fn get_default_provider() -> String {
     // .into()
 }
 
-pub fn read_audio_file(path: &str) -> Result<(Vec<f32>, u32)> {
-    let mut reader = hound::WavReader::open(path)?;
-    let sample_rate = reader.spec().sample_rate;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelSelection {
+    /// Average all channels into mono
+    Downmix,
+    /// Keep a single channel by index
+    Select(usize),
+}
 
-    // Check if the sample rate is 16000
-    if sample_rate != 16000 {
-        bail!("The sample rate must be 16000.");
+#[derive(Debug, Clone)]
+pub struct ReadAudioOptions {
+    /// Resample to this rate. `None` keeps the rate of the file.
+    pub sample_rate: Option<u32>,
+    pub channels: ChannelSelection,
+}
+
+impl Default for ReadAudioOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: Some(16000),
+            channels: ChannelSelection::Downmix,
+        }
     }
+}
 
-    // Collect samples into a Vec<f32>
-    let samples: Vec<f32> = reader
-        .samples::<i16>()
-        .map(|s| (s.unwrap() as f32) / (i16::MAX as f32))
-        .collect();
+/// Errors returned (wrapped in [`eyre::Report`]) by the audio helpers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioError {
+    UnsupportedFormat {
+        float: bool,
+        bits_per_sample: u16,
+        channels: u16,
+        sample_rate: u32,
+    },
+    InvalidChannel {
+        channel: usize,
+        channels: u16,
+    },
+    UnsupportedCodec {
+        codec: String,
+    },
+}
 
-    Ok((samples, sample_rate))
+impl std::fmt::Display for AudioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioError::UnsupportedFormat {
+                float,
+                bits_per_sample,
+                channels,
+                sample_rate,
+            } => write!(
+                f,
+                "unsupported wav format: {}-bit {}, {} channel(s), {} Hz (supported: 8/16/24/32-bit int, 32-bit float)",
+                bits_per_sample,
+                if *float { "float" } else { "int" },
+                channels,
+                sample_rate
+            ),
+            AudioError::InvalidChannel { channel, channels } => write!(
+                f,
+                "channel {} requested but the file has {} channel(s)",
+                channel, channels
+            ),
+            AudioError::UnsupportedCodec { codec } => {
+                write!(f, "no decoder available for codec {}", codec)
+            }
+        }
+    }
 }
 
-pub fn write_audio_file(path: &str, samples: &[f32], sample_rate: u32) -> Result<()> {
-    // Create a WAV file writer
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
+impl std::error::Error for AudioError {}
+
+/// Read an audio file as mono 16kHz samples.
+///
+/// Only wav is supported unless the `decode` feature is enabled.
+pub fn read_audio_file(path: &str) -> Result<(Vec<f32>, u32)> {
+    read_audio_file_with_options(path, ReadAudioOptions::default())
+}
+
+pub fn read_audio_file_with_options(
+    path: &str,
+    options: ReadAudioOptions,
+) -> Result<(Vec<f32>, u32)> {
+    #[cfg(feature = "decode")]
+    {
+        let is_wav = std::path::Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        if !is_wav {
+            return decode::decode_audio_file(path, options);
+        }
+    }
+
+    let (interleaved, channels, sample_rate) = read_wav(path)?;
+    convert_audio(interleaved, channels, sample_rate, &options)
+}
+
+fn read_wav(path: &str) -> Result<(Vec<f32>, u16, u32)> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let interleaved: Vec<f32> = match wav_int_scale(&spec)? {
+        Some(scale) => reader
+            .samples::<i32>()
+            .map(|s| s.map(|s| s as f32 / scale))
+            .collect::<std::result::Result<_, _>>()?,
+        None => reader
+            .samples::<f32>()
+            .collect::<std::result::Result<_, _>>()?,
     };
 
-    let mut writer = hound::WavWriter::create(path, spec)?;
+    Ok((interleaved, spec.channels, spec.sample_rate))
+}
 
-    // Convert samples from f32 to i16 and write them to the WAV file
-    for &sample in samples {
-        let scaled_sample =
-            (sample * (i16::MAX as f32)).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
-        writer.write_sample(scaled_sample)?;
+/// Scale to normalize integer samples, `None` for float wav.
+pub(crate) fn wav_int_scale(spec: &hound::WavSpec) -> Result<Option<f32>> {
+    match (spec.sample_format, spec.bits_per_sample) {
+        (hound::SampleFormat::Int, 8 | 16 | 24 | 32) => {
+            Ok(Some((1u64 << (spec.bits_per_sample - 1)) as f32))
+        }
+        (hound::SampleFormat::Float, 32) => Ok(None),
+        _ => Err(AudioError::UnsupportedFormat {
+            float: spec.sample_format == hound::SampleFormat::Float,
+            bits_per_sample: spec.bits_per_sample,
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+        }
+        .into()),
     }
+}
+
+/// Apply the channel selection and target sample rate of `options` to interleaved samples.
+pub(crate) fn convert_audio(
+    interleaved: Vec<f32>,
+    channels: u16,
+    sample_rate: u32,
+    options: &ReadAudioOptions,
+) -> Result<(Vec<f32>, u32)> {
+    let samples = select_channels(interleaved, channels, options.channels)?;
+
+    match options.sample_rate {
+        Some(target) if target != sample_rate => {
+            Ok((resampler::resample(&samples, sample_rate, target)?, target))
+        }
+        _ => Ok((samples, sample_rate)),
+    }
+}
+
+pub(crate) fn select_channels(
+    interleaved: Vec<f32>,
+    channels: u16,
+    selection: ChannelSelection,
+) -> Result<Vec<f32>> {
+    let num_channels = channels as usize;
+    let samples = match selection {
+        ChannelSelection::Downmix | ChannelSelection::Select(0) if num_channels == 1 => interleaved,
+        ChannelSelection::Downmix => interleaved
+            .chunks_exact(num_channels)
+            .map(|frame| frame.iter().sum::<f32>() / num_channels as f32)
+            .collect(),
+        ChannelSelection::Select(channel) => {
+            if channel >= num_channels {
+                return Err(AudioError::InvalidChannel { channel, channels }.into());
+            }
+            interleaved
+                .chunks_exact(num_channels)
+                .map(|frame| frame[channel])
+                .collect()
+        }
+    };
+    Ok(samples)
+}
+
+/// Write mono samples as a 16-bit wav file.
+pub fn write_audio_file(path: &str, samples: &[f32], sample_rate: u32) -> Result<()> {
+    write_audio_file_with_options(path, samples, sample_rate, WriteAudioOptions::default())
+}
 
-    writer.finalize()?;
-    Ok(())
+/// Write interleaved samples with the format, channels and container in `options`.
+pub fn write_audio_file_with_options(
+    path: &str,
+    samples: &[f32],
+    sample_rate: u32,
+    options: WriteAudioOptions,
+) -> Result<()> {
+    let mut writer = AudioWriter::create(path, sample_rate, options)?;
+    writer.write(samples)?;
+    writer.finalize()
 }
 
 pub struct OnnxConfig {
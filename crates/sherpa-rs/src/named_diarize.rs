@@ -0,0 +1,280 @@
+use eyre::{bail, Result};
+use std::path::Path;
+
+use crate::{
+    der,
+    diarize::{CancellationToken, Diarize, DiarizeConfig, Segment},
+    embedding_manager::EmbeddingManager,
+    speaker_id::{EmbeddingExtractor, ExtractorConfig, DEFAULT_SIMILARITY_THRESHOLD},
+    utils::normalize_embedding,
+};
+
+/// Name of speakers that don't match anyone enrolled
+pub const UNKNOWN_SPEAKER: &str = "unknown";
+
+#[derive(Debug, Clone)]
+pub struct NamedDiarizeConfig {
+    /// Similarity to an enrolled person below which a speaker is unknown
+    pub threshold: f32,
+    /// Cluster into as many speakers as are enrolled, instead of the
+    /// clustering of `DiarizeConfig`
+    pub force_num_clusters: bool,
+    /// Give each enrolled name to at most one speaker
+    pub one_to_one: bool,
+    /// Shorter segments aren't embedded, unless a speaker has no longer one
+    pub min_segment_duration: f32,
+    /// Seconds of audio embedded per speaker, longest segments first
+    pub max_speaker_duration: f32,
+}
+
+impl Default for NamedDiarizeConfig {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_SIMILARITY_THRESHOLD,
+            force_num_clusters: false,
+            one_to_one: true,
+            min_segment_duration: 0.5,
+            max_speaker_duration: 30.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NamedSegment {
+    pub start: f32,
+    pub end: f32,
+    /// Cluster index from diarization
+    pub speaker: i32,
+    /// Enrolled name, or [`UNKNOWN_SPEAKER`]
+    pub name: String,
+    /// Similarity of the speaker to `name`, `None` when unknown
+    pub score: Option<f32>,
+}
+
+impl NamedSegment {
+    pub fn is_known(&self) -> bool {
+        self.score.is_some()
+    }
+}
+
+/// Diarization that labels speakers with the names of enrolled people.
+///
+/// After clustering, the embeddings of each speaker's segments are averaged and
+/// matched against an [`EmbeddingManager`]. Enrolled embeddings must come from the
+/// same embedding model, a manager of another dimension is rejected.
+pub struct NamedDiarize {
+    diarize: Diarize,
+    extractor: EmbeddingExtractor,
+    config: NamedDiarizeConfig,
+    num_clusters: Option<i32>,
+    clustering_threshold: f32,
+    sample_rate: u32,
+}
+
+impl NamedDiarize {
+    pub fn new<P: AsRef<Path>>(
+        segmentation_model: P,
+        embedding_model: P,
+        diarize_config: DiarizeConfig,
+        config: NamedDiarizeConfig,
+    ) -> Result<Self> {
+        let extractor = EmbeddingExtractor::new(ExtractorConfig {
+            model: embedding_model.as_ref().to_string_lossy().into_owned(),
+            provider: diarize_config.provider.clone(),
+            num_threads: diarize_config.embedding_num_threads.map(|n| n as usize),
+            debug: diarize_config.debug,
+        })?;
+        let num_clusters = diarize_config.num_clusters;
+        let clustering_threshold = diarize_config.threshold.unwrap_or(0.5);
+        let diarize = Diarize::new(segmentation_model, embedding_model, diarize_config)?;
+        let sample_rate = diarize.sample_rate();
+
+        Ok(Self {
+            diarize,
+            extractor,
+            config,
+            num_clusters,
+            clustering_threshold,
+            sample_rate,
+        })
+    }
+
+    /// Sample rate the samples passed to `compute` must have
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Dimension of the embeddings the enrolled people need
+    pub fn embedding_size(&self) -> usize {
+        self.extractor.embedding_size
+    }
+
+    /// See [`Diarize::set_clustering`]. Ignored while `force_num_clusters` is set
+    /// and people are enrolled.
+    pub fn set_clustering(&mut self, num_clusters: Option<i32>, threshold: f32) {
+        self.num_clusters = num_clusters;
+        self.clustering_threshold = threshold;
+        self.diarize.set_clustering(num_clusters, threshold);
+    }
+
+    pub fn compute(
+        &mut self,
+        samples: &[f32],
+        enrolled: &mut EmbeddingManager,
+    ) -> Result<Vec<NamedSegment>> {
+        self.compute_with_callback(samples, enrolled, None, |_, _| true)
+    }
+
+    /// Like `compute` with progress and cancellation, see [`Diarize::compute_with_callback`].
+    pub fn compute_with_callback<F: FnMut(i32, i32) -> bool>(
+        &mut self,
+        samples: &[f32],
+        enrolled: &mut EmbeddingManager,
+        cancel: Option<&CancellationToken>,
+        callback: F,
+    ) -> Result<Vec<NamedSegment>> {
+        self.check_dimension(enrolled)?;
+        let num_enrolled = enrolled.num_speakers();
+        let forced = self.config.force_num_clusters && num_enrolled > 0;
+        if forced {
+            self.diarize
+                .set_clustering(Some(num_enrolled), self.clustering_threshold);
+        }
+        let segments = self
+            .diarize
+            .compute_with_callback(samples, cancel, callback);
+        if forced {
+            self.diarize
+                .set_clustering(self.num_clusters, self.clustering_threshold);
+        }
+        self.name_segments(samples, &segments?, enrolled)
+    }
+
+    /// Label segments from any diarization of `samples` with enrolled names.
+    ///
+    /// Fails if `enrolled` isn't of the dimension of the embedding model.
+    pub fn name_segments(
+        &mut self,
+        samples: &[f32],
+        segments: &[Segment],
+        enrolled: &mut EmbeddingManager,
+    ) -> Result<Vec<NamedSegment>> {
+        self.check_dimension(enrolled)?;
+        let centroids = self.centroids(samples, segments);
+        let names = enrolled.all_speakers();
+
+        // Similarity of each speaker to each enrolled person
+        let scores: Vec<Vec<f32>> = centroids
+            .iter()
+            .map(|centroid| {
+                let mut row = vec![-1.0; names.len()];
+                if let Some(centroid) = centroid.as_ref().filter(|_| !names.is_empty()) {
+                    for m in enrolled.get_best_matches(centroid, -1.0, names.len() as i32) {
+                        if let Some(person) = names.iter().position(|name| *name == m.name) {
+                            row[person] = m.score;
+                        }
+                    }
+                }
+                row
+            })
+            .collect();
+
+        let assignment: Vec<Option<usize>> = if self.config.one_to_one {
+            let cost: Vec<Vec<f64>> = scores
+                .iter()
+                .map(|row| row.iter().map(|&score| -score as f64).collect())
+                .collect();
+            der::hungarian(&cost)
+        } else {
+            scores
+                .iter()
+                .map(|row| (0..row.len()).max_by(|&a, &b| row[a].total_cmp(&row[b])))
+                .collect()
+        };
+        let labels: Vec<Option<(&str, f32)>> = assignment
+            .iter()
+            .zip(&scores)
+            .zip(&centroids)
+            .map(|((person, row), centroid)| {
+                let person = person.filter(|_| centroid.is_some())?;
+                (row[person] >= self.config.threshold)
+                    .then(|| (names[person].as_str(), row[person]))
+            })
+            .collect();
+
+        Ok(segments
+            .iter()
+            .map(|segment| {
+                let label = usize::try_from(segment.speaker)
+                    .ok()
+                    .and_then(|speaker| *labels.get(speaker)?);
+                NamedSegment {
+                    start: segment.start,
+                    end: segment.end,
+                    speaker: segment.speaker,
+                    name: label.map_or(UNKNOWN_SPEAKER, |(name, _)| name).to_string(),
+                    score: label.map(|(_, score)| score),
+                }
+            })
+            .collect())
+    }
+
+    /// The native manager reads its own dimension's worth of floats from each centroid
+    fn check_dimension(&self, enrolled: &EmbeddingManager) -> Result<()> {
+        if enrolled.dimension() as usize != self.extractor.embedding_size {
+            bail!(
+                "enrolled embeddings have dimension {} but the embedding model has {}",
+                enrolled.dimension(),
+                self.extractor.embedding_size
+            );
+        }
+        Ok(())
+    }
+
+    /// Normalized mean embedding of each speaker, weighted by segment duration.
+    /// `None` for speakers without audio long enough to embed.
+    fn centroids(&mut self, samples: &[f32], segments: &[Segment]) -> Vec<Option<Vec<f32>>> {
+        let sample_rate = self.sample_rate as f32;
+        let num_speakers = segments
+            .iter()
+            .map(|segment| segment.speaker + 1)
+            .max()
+            .unwrap_or(0)
+            .max(0) as usize;
+
+        let mut centroids = Vec::with_capacity(num_speakers);
+        for speaker in 0..num_speakers {
+            let mut own: Vec<&Segment> = segments
+                .iter()
+                .filter(|segment| segment.speaker == speaker as i32)
+                .collect();
+            own.sort_by(|a, b| (b.end - b.start).total_cmp(&(a.end - a.start)));
+
+            let mut sum = vec![0.0; self.extractor.embedding_size];
+            let mut duration = 0.0;
+            for (i, segment) in own.iter().enumerate() {
+                let length = segment.end - segment.start;
+                if duration >= self.config.max_speaker_duration
+                    || (i > 0 && length < self.config.min_segment_duration)
+                {
+                    break;
+                }
+                let start = ((segment.start * sample_rate) as usize).min(samples.len());
+                let end = ((segment.end * sample_rate) as usize).clamp(start, samples.len());
+                // Too short for the extractor
+                let Ok(embedding) = self
+                    .extractor
+                    .compute_speaker_embedding(samples[start..end].to_vec(), self.sample_rate)
+                else {
+                    continue;
+                };
+                for (sum, value) in sum.iter_mut().zip(normalize_embedding(embedding)) {
+                    *sum += value * length;
+                }
+                duration += length;
+            }
+            centroids.push((duration > 0.0).then(|| normalize_embedding(sum)));
+        }
+        centroids
+    }
+}
@@ -0,0 +1,212 @@
+use eyre::Result;
+
+use crate::{
+    diarize::Segment,
+    embedding_manager::EmbeddingManager,
+    silero_vad::SileroVadConfig,
+    speaker_id::{EmbeddingExtractor, ExtractorConfig, DEFAULT_SIMILARITY_THRESHOLD},
+    utils::normalize_embedding,
+    vad::{self, VadConfig, VoiceActivityDetector},
+};
+
+#[derive(Debug)]
+pub struct OnlineDiarizeConfig {
+    pub vad: VadConfig,
+    pub extractor: ExtractorConfig,
+    /// Similarity to the closest speaker below which a new speaker is created
+    pub threshold: f32,
+    /// Once reached, segments below `threshold` go to the closest speaker without moving
+    /// its centroid instead of creating a speaker. Matches still update the centroid.
+    pub max_speakers: Option<usize>,
+    /// Shorter segments get the closest speaker (or [`UNKNOWN_SPEAKER`] before the first
+    /// one) but don't create speakers or move centroids, their embeddings are unreliable
+    pub min_embedding_duration: f32,
+}
+
+impl Default for OnlineDiarizeConfig {
+    fn default() -> Self {
+        Self {
+            vad: VadConfig::Silero(SileroVadConfig::default()),
+            extractor: ExtractorConfig::default(),
+            threshold: DEFAULT_SIMILARITY_THRESHOLD,
+            max_speakers: None,
+            min_embedding_duration: 1.0,
+        }
+    }
+}
+
+/// Speaker of segments without a usable embedding, eg. too short for the extractor
+pub const UNKNOWN_SPEAKER: i32 = -1;
+
+/// Speech segment labeled with a speaker, positioned in the input stream.
+#[derive(Debug, Clone)]
+pub struct OnlineSegment {
+    pub start: f32,
+    pub end: f32,
+    /// Speaker index, or [`UNKNOWN_SPEAKER`]
+    pub speaker: i32,
+    pub samples: Vec<f32>,
+}
+
+impl OnlineSegment {
+    pub fn to_segment(&self) -> Segment {
+        Segment {
+            start: self.start,
+            end: self.end,
+            speaker: self.speaker,
+        }
+    }
+}
+
+/// Streaming diarization: speech segments from the VAD are labeled as they arrive
+/// by matching their embedding against the centroids of the speakers seen so far.
+pub struct OnlineDiarize {
+    vad: Box<dyn VoiceActivityDetector>,
+    extractor: EmbeddingExtractor,
+    manager: EmbeddingManager,
+    /// Sum of the embeddings of each speaker
+    speakers: Vec<Vec<f32>>,
+    threshold: f32,
+    max_speakers: Option<usize>,
+    min_embedding_duration: f32,
+    sample_rate: u32,
+}
+
+impl OnlineDiarize {
+    pub fn new(config: OnlineDiarizeConfig) -> Result<Self> {
+        let buffer_size_in_seconds = config.vad.buffer_size_in_seconds();
+        let vad = vad::create(config.vad, buffer_size_in_seconds)?;
+        let extractor = EmbeddingExtractor::new(config.extractor)?;
+        let manager = EmbeddingManager::new(extractor.embedding_size as i32);
+        let sample_rate = vad.sample_rate();
+
+        Ok(Self {
+            vad,
+            extractor,
+            manager,
+            speakers: Vec::new(),
+            threshold: config.threshold,
+            max_speakers: config.max_speakers,
+            min_embedding_duration: config.min_embedding_duration,
+            sample_rate,
+        })
+    }
+
+    /// Sample rate of the samples passed to `accept_waveform`
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn num_speakers(&self) -> usize {
+        self.speakers.len()
+    }
+
+    /// Normalized mean embedding of a speaker
+    pub fn centroid(&self, speaker: i32) -> Option<Vec<f32>> {
+        let speaker = self.speakers.get(usize::try_from(speaker).ok()?)?;
+        Some(normalize_embedding(speaker.clone()))
+    }
+
+    /// Feed audio of any length, returns the segments that ended in it.
+    pub fn accept_waveform(&mut self, samples: &[f32]) -> Vec<OnlineSegment> {
+        self.vad.accept_waveform(samples.to_vec());
+        self.take_segments()
+    }
+
+    /// Return the segment still in progress, eg. at the end of the stream.
+    pub fn flush(&mut self) -> Vec<OnlineSegment> {
+        self.vad.flush();
+        self.take_segments()
+    }
+
+    /// Forget the pending audio and the speakers, eg. for a new meeting.
+    pub fn reset(&mut self) {
+        self.vad.clear();
+        for speaker in 0..self.speakers.len() {
+            self.manager.remove(&speaker.to_string());
+        }
+        self.speakers.clear();
+    }
+
+    fn take_segments(&mut self) -> Vec<OnlineSegment> {
+        let mut segments = Vec::new();
+        while !self.vad.is_empty() {
+            let segment = self.vad.front();
+            self.vad.pop();
+
+            let speaker = self.assign(&segment.samples);
+            let start = segment.start as f32 / self.sample_rate as f32;
+            let end = start + segment.samples.len() as f32 / self.sample_rate as f32;
+            segments.push(OnlineSegment {
+                start,
+                end,
+                speaker,
+                samples: segment.samples,
+            });
+        }
+        segments
+    }
+
+    fn assign(&mut self, samples: &[f32]) -> i32 {
+        let embedding = match self
+            .extractor
+            .compute_speaker_embedding(samples.to_vec(), self.sample_rate)
+        {
+            Ok(embedding) => normalize_embedding(embedding),
+            Err(err) => {
+                tracing::warn!("no speaker embedding for segment: {}", err);
+                return UNKNOWN_SPEAKER;
+            }
+        };
+
+        let reliable =
+            samples.len() as f32 / self.sample_rate as f32 >= self.min_embedding_duration;
+        let full = self
+            .max_speakers
+            .is_some_and(|max| self.speakers.len() >= max);
+        // Scores are cosine similarities, so -1 returns the closest speaker
+        let closest = self
+            .manager
+            .get_best_matches(&embedding, -1.0, 1)
+            .into_iter()
+            .find_map(|m| Some((m.name.parse::<i32>().ok()?, m.score)));
+
+        match closest {
+            Some((speaker, score)) if score >= self.threshold => {
+                if reliable {
+                    self.update(speaker, &embedding);
+                }
+                speaker
+            }
+            // No match, but no new speaker either: closest one, without moving its centroid
+            Some((speaker, _)) if full || !reliable => speaker,
+            None if full || !reliable => UNKNOWN_SPEAKER,
+            _ => {
+                let speaker = self.speakers.len() as i32;
+                if let Err(err) = self
+                    .manager
+                    .add(speaker.to_string(), &mut embedding.clone())
+                {
+                    tracing::warn!("failed to add speaker {}: {}", speaker, err);
+                    return UNKNOWN_SPEAKER;
+                }
+                self.speakers.push(embedding);
+                speaker
+            }
+        }
+    }
+
+    fn update(&mut self, speaker: i32, embedding: &[f32]) {
+        let sum = &mut self.speakers[speaker as usize];
+        for (sum, value) in sum.iter_mut().zip(embedding) {
+            *sum += value;
+        }
+
+        let mut centroid = normalize_embedding(sum.clone());
+        let name = speaker.to_string();
+        self.manager.remove(&name);
+        if let Err(err) = self.manager.add(name, &mut centroid) {
+            tracing::warn!("failed to update speaker {}: {}", speaker, err);
+        }
+    }
+}
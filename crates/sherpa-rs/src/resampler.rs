@@ -0,0 +1,117 @@
+use eyre::{bail, Result};
+
+/// Band-limited linear resampler from sherpa-onnx.
+///
+/// Use [`Resampler::resample`] for whole buffers or [`Resampler::resample_chunk`]
+/// to convert a stream chunk by chunk, keeping the filter state between calls.
+#[derive(Debug)]
+pub struct Resampler {
+    resampler: *const sherpa_rs_sys::SherpaOnnxLinearResampler,
+}
+
+impl Resampler {
+    pub fn new(input_sample_rate: u32, output_sample_rate: u32) -> Result<Self> {
+        if input_sample_rate == 0 || output_sample_rate == 0 {
+            bail!(
+                "Invalid sample rates {} -> {}",
+                input_sample_rate,
+                output_sample_rate
+            );
+        }
+        let cutoff = 0.99 * 0.5 * input_sample_rate.min(output_sample_rate) as f32;
+        let num_zeros = 6;
+        let resampler = unsafe {
+            sherpa_rs_sys::SherpaOnnxCreateLinearResampler(
+                input_sample_rate as i32,
+                output_sample_rate as i32,
+                cutoff,
+                num_zeros,
+            )
+        };
+        if resampler.is_null() {
+            bail!(
+                "Failed to create resampler from {} to {}",
+                input_sample_rate,
+                output_sample_rate
+            );
+        }
+        Ok(Self { resampler })
+    }
+
+    pub fn input_sample_rate(&self) -> u32 {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxLinearResamplerResampleGetInputSampleRate(self.resampler)
+                as u32
+        }
+    }
+
+    pub fn output_sample_rate(&self) -> u32 {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxLinearResamplerResampleGetOutputSampleRate(self.resampler)
+                as u32
+        }
+    }
+
+    /// Resample a complete buffer. Any state left from previous chunks is discarded.
+    pub fn resample(&mut self, samples: &[f32]) -> Vec<f32> {
+        self.reset();
+        self.resample_chunk(samples, true)
+    }
+
+    /// Resample the next chunk of a stream. Pass `flush` with the last chunk to
+    /// drain the filter; the resampler is then ready for a new stream.
+    pub fn resample_chunk(&mut self, samples: &[f32], flush: bool) -> Vec<f32> {
+        unsafe {
+            let out_ptr = sherpa_rs_sys::SherpaOnnxLinearResamplerResample(
+                self.resampler,
+                samples.as_ptr(),
+                samples.len() as i32,
+                flush.into(),
+            );
+            if out_ptr.is_null() {
+                return Vec::new();
+            }
+            let out = out_ptr.read();
+            let resampled = if out.samples.is_null() || out.n <= 0 {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(out.samples, out.n as usize).to_vec()
+            };
+
+            // Free
+            sherpa_rs_sys::SherpaOnnxLinearResamplerResampleFree(out_ptr);
+
+            resampled
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxLinearResamplerReset(self.resampler);
+        }
+    }
+}
+
+/// Resample `samples` from `input_sample_rate` to `output_sample_rate` in one go.
+pub fn resample(
+    samples: &[f32],
+    input_sample_rate: u32,
+    output_sample_rate: u32,
+) -> Result<Vec<f32>> {
+    if input_sample_rate == output_sample_rate {
+        return Ok(samples.to_vec());
+    }
+    let mut resampler = Resampler::new(input_sample_rate, output_sample_rate)?;
+    Ok(resampler.resample(samples))
+}
+
+unsafe impl Send for Resampler {}
+unsafe impl Sync for Resampler {}
+
+impl Drop for Resampler {
+    fn drop(&mut self) {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxDestroyLinearResampler(self.resampler);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, fmt::Write};
+
+use eyre::{bail, Result};
+
+use crate::diarize::Segment;
+
+/// Segments of one recording in an RTTM file.
+#[derive(Debug, Clone, Default)]
+pub struct RttmFile {
+    pub file_id: String,
+    /// Speaker names from the file, `Segment::speaker` indexes into it
+    pub speakers: Vec<String>,
+    pub segments: Vec<Segment>,
+}
+
+/// Read the `SPEAKER` lines of an RTTM file, grouped by file id in order of appearance.
+pub fn read_rttm(path: &str) -> Result<Vec<RttmFile>> {
+    parse_rttm(&std::fs::read_to_string(path)?)
+}
+
+pub fn parse_rttm(text: &str) -> Result<Vec<RttmFile>> {
+    let mut files: Vec<RttmFile> = Vec::new();
+    let mut file_index = HashMap::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.first() != Some(&"SPEAKER") {
+            continue;
+        }
+        if fields.len() < 8 {
+            bail!("line {}: expected at least 8 fields", line_number + 1);
+        }
+        let (Ok(start), Ok(duration)) = (fields[3].parse::<f32>(), fields[4].parse::<f32>()) else {
+            bail!("line {}: invalid onset or duration", line_number + 1);
+        };
+
+        let index = *file_index.entry(fields[1].to_string()).or_insert_with(|| {
+            files.push(RttmFile {
+                file_id: fields[1].to_string(),
+                ..Default::default()
+            });
+            files.len() - 1
+        });
+        let file = &mut files[index];
+        let speaker = match file.speakers.iter().position(|name| name == fields[7]) {
+            Some(speaker) => speaker,
+            None => {
+                file.speakers.push(fields[7].to_string());
+                file.speakers.len() - 1
+            }
+        };
+        file.segments.push(Segment {
+            start,
+            end: start + duration,
+            speaker: speaker as i32,
+        });
+    }
+    Ok(files)
+}
+
+/// Format segments as RTTM. Speakers are named by `speakers[segment.speaker]` when
+/// given, otherwise `speaker_<n>`.
+pub fn format_rttm(file_id: &str, segments: &[Segment], speakers: Option<&[String]>) -> String {
+    let mut rttm = String::new();
+    for segment in segments {
+        let name = speakers
+            .and_then(|names| names.get(usize::try_from(segment.speaker).ok()?))
+            .cloned()
+            .unwrap_or_else(|| format!("speaker_{}", segment.speaker));
+        writeln!(
+            rttm,
+            "SPEAKER {} 1 {:.3} {:.3} <NA> <NA> {} <NA> <NA>",
+            file_id,
+            segment.start,
+            segment.end - segment.start,
+            name
+        )
+        .unwrap();
+    }
+    rttm
+}
+
+pub fn write_rttm(
+    path: &str,
+    file_id: &str,
+    segments: &[Segment],
+    speakers: Option<&[String]>,
+) -> Result<()> {
+    std::fs::write(path, format_rttm(file_id, segments, speakers))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_parse() {
+        let segments = vec![
+            Segment {
+                start: 0.5,
+                end: 2.25,
+                speaker: 1,
+            },
+            Segment {
+                start: 3.0,
+                end: 4.0,
+                speaker: 0,
+            },
+        ];
+        let speakers = vec!["alice".to_string(), "bob".to_string()];
+        let rttm = format_rttm("meeting", &segments, Some(&speakers));
+        assert_eq!(
+            rttm.lines().next(),
+            Some("SPEAKER meeting 1 0.500 1.750 <NA> <NA> bob <NA> <NA>")
+        );
+
+        let files = parse_rttm(&rttm).unwrap();
+        assert_eq!(files.len(), 1);
+        let file = &files[0];
+        assert_eq!(file.file_id, "meeting");
+        // Speakers are numbered in order of appearance
+        assert_eq!(file.speakers, ["bob", "alice"]);
+        let parsed: Vec<(f32, f32, &str)> = file
+            .segments
+            .iter()
+            .map(|s| (s.start, s.end, file.speakers[s.speaker as usize].as_str()))
+            .collect();
+        assert_eq!(parsed, [(0.5, 2.25, "bob"), (3.0, 4.0, "alice")]);
+    }
+
+    #[test]
+    fn groups_files_and_skips_other_lines() {
+        let rttm = "\
+SPKR-INFO a 1 <NA> <NA> <NA> unknown x <NA> <NA>
+SPEAKER a 1 0.0 1.0 <NA> <NA> x <NA> <NA>
+SPEAKER b 1 2.0 1.0 <NA> <NA> y <NA> <NA>
+SPEAKER a 1 5.0 1.0 <NA> <NA> x <NA> <NA>
+";
+        let files = parse_rttm(rttm).unwrap();
+        let ids: Vec<&str> = files.iter().map(|f| f.file_id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(files[0].segments.len(), 2);
+        assert_eq!(files[1].speakers, ["y"]);
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert!(parse_rttm("SPEAKER a 1 0.0").is_err());
+        assert!(parse_rttm("SPEAKER a 1 x 1.0 <NA> <NA> s <NA> <NA>").is_err());
+    }
+
+    #[test]
+    fn unnamed_speakers() {
+        let segments = [Segment {
+            start: 1.0,
+            end: 2.0,
+            speaker: 3,
+        }];
+        assert_eq!(
+            format_rttm("f", &segments, None),
+            "SPEAKER f 1 1.000 1.000 <NA> <NA> speaker_3 <NA> <NA>\n"
+        );
+    }
+}
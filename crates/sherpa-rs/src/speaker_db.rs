@@ -0,0 +1,377 @@
+use eyre::Result;
+use std::path::Path;
+
+use crate::embedding_manager::EmbeddingManager;
+
+const MAGIC: &[u8; 8] = b"SHERPASD";
+
+/// Version of the speaker database format written by [`EmbeddingManager::save`].
+///
+/// Little endian layout:
+///
+/// ```text
+/// magic "SHERPASD", version u32, dimension u32,
+/// model name (u32 length + utf-8), model hash u64,
+/// number of speakers u32, then for each speaker:
+///     name (u32 length + utf-8), number of embeddings u32, embeddings f32[n * dimension]
+/// ```
+pub const SPEAKER_DB_VERSION: u32 = 1;
+
+/// Identifies the extractor model embeddings were computed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelIdentity {
+    /// Model file name, informative only
+    pub name: String,
+    /// FNV-1a hash of the model file
+    pub hash: u64,
+    /// Embedding dimension of the model
+    pub dimension: usize,
+}
+
+impl ModelIdentity {
+    /// `dimension` is the `embedding_size` of the extractor using the model.
+    pub fn from_model<P: AsRef<Path>>(path: P, dimension: usize) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Self {
+            name,
+            hash: fnv1a(&bytes),
+            dimension,
+        })
+    }
+}
+
+impl std::fmt::Display for ModelIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:016x})", self.name, self.hash)
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Errors returned (wrapped in [`eyre::Report`]) when loading a speaker database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpeakerDbError {
+    InvalidFormat {
+        reason: String,
+    },
+    UnsupportedVersion {
+        version: u32,
+    },
+    ModelMismatch {
+        expected: ModelIdentity,
+        found: ModelIdentity,
+    },
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for SpeakerDbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpeakerDbError::InvalidFormat { reason } => {
+                write!(f, "invalid speaker database: {}", reason)
+            }
+            SpeakerDbError::UnsupportedVersion { version } => write!(
+                f,
+                "speaker database version {} is newer than the supported version {}",
+                version, SPEAKER_DB_VERSION
+            ),
+            SpeakerDbError::ModelMismatch { expected, found } => write!(
+                f,
+                "speaker database was created with model {} but {} is used",
+                found, expected
+            ),
+            SpeakerDbError::DimensionMismatch { expected, found } => write!(
+                f,
+                "speaker database has {}-dimensional embeddings but the model has {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpeakerDbError {}
+
+type Speakers = Vec<(String, Vec<Vec<f32>>)>;
+
+impl EmbeddingManager {
+    /// Save the enrolled speakers with all their embeddings.
+    pub fn save<P: AsRef<Path>>(&self, path: P, model: &ModelIdentity) -> Result<()> {
+        std::fs::write(path, self.to_bytes(model)?)?;
+        Ok(())
+    }
+
+    /// Load speakers saved with `save`. Fails with [`SpeakerDbError::ModelMismatch`] when
+    /// they were computed by a different model than `model`.
+    pub fn load<P: AsRef<Path>>(path: P, model: &ModelIdentity) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?, model)
+    }
+
+    /// Fails if the dimension of the manager isn't the one of `model`.
+    pub fn to_bytes(&self, model: &ModelIdentity) -> Result<Vec<u8>> {
+        if self.dimension() as usize != model.dimension {
+            return Err(SpeakerDbError::DimensionMismatch {
+                expected: model.dimension,
+                found: self.dimension() as usize,
+            }
+            .into());
+        }
+        Ok(encode(model, self.speakers()))
+    }
+
+    pub fn from_bytes(bytes: &[u8], model: &ModelIdentity) -> Result<Self> {
+        let speakers = decode(bytes, model)?;
+        let mut manager = EmbeddingManager::new(model.dimension as i32);
+        for (name, embeddings) in speakers {
+            manager.add_list(name, &embeddings)?;
+        }
+        Ok(manager)
+    }
+}
+
+fn encode<'a>(
+    model: &ModelIdentity,
+    speakers: impl ExactSizeIterator<Item = (&'a str, &'a [Vec<f32>])>,
+) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(SPEAKER_DB_VERSION.to_le_bytes());
+    bytes.extend((model.dimension as u32).to_le_bytes());
+    write_str(&mut bytes, &model.name);
+    bytes.extend(model.hash.to_le_bytes());
+
+    bytes.extend((speakers.len() as u32).to_le_bytes());
+    for (name, embeddings) in speakers {
+        write_str(&mut bytes, name);
+        bytes.extend((embeddings.len() as u32).to_le_bytes());
+        for value in embeddings.iter().flatten() {
+            bytes.extend(value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn decode(bytes: &[u8], model: &ModelIdentity) -> Result<Speakers> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid("not a speaker database"));
+    }
+    let version = reader.u32()?;
+    if version == 0 {
+        return Err(invalid("version is 0"));
+    }
+    if version > SPEAKER_DB_VERSION {
+        return Err(SpeakerDbError::UnsupportedVersion { version }.into());
+    }
+    let dimension = reader.u32()? as usize;
+    let found = ModelIdentity {
+        name: reader.string()?,
+        hash: u64::from_le_bytes(reader.take(8)?.try_into().unwrap()),
+        dimension,
+    };
+    if found.hash != model.hash {
+        return Err(SpeakerDbError::ModelMismatch {
+            expected: model.clone(),
+            found,
+        }
+        .into());
+    }
+    if dimension != model.dimension {
+        return Err(SpeakerDbError::DimensionMismatch {
+            expected: model.dimension,
+            found: dimension,
+        }
+        .into());
+    }
+    if dimension == 0 {
+        return Err(invalid("embedding dimension is 0"));
+    }
+
+    let mut speakers = Vec::new();
+    for _ in 0..reader.u32()? {
+        let name = reader.string()?;
+        let num_embeddings = reader.u32()? as usize;
+        if num_embeddings == 0 {
+            return Err(invalid("speaker without embeddings"));
+        }
+        let len = num_embeddings
+            .checked_mul(dimension * 4)
+            .ok_or_else(|| invalid("too many embeddings"))?;
+        let embeddings = reader
+            .take(len)?
+            .chunks_exact(dimension * 4)
+            .map(|embedding| {
+                embedding
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect()
+            })
+            .collect();
+        speakers.push((name, embeddings));
+    }
+    if !reader.bytes.is_empty() {
+        return Err(invalid("trailing data"));
+    }
+    Ok(speakers)
+}
+
+fn invalid(reason: &str) -> eyre::Report {
+    SpeakerDbError::InvalidFormat {
+        reason: reason.into(),
+    }
+    .into()
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend((value.len() as u32).to_le_bytes());
+    bytes.extend(value.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.bytes.len() {
+            return Err(invalid("unexpected end of file"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("name is not utf-8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> ModelIdentity {
+        ModelIdentity {
+            name: "model.onnx".into(),
+            hash: 0x0123456789abcdef,
+            dimension: 3,
+        }
+    }
+
+    fn speakers() -> Speakers {
+        vec![
+            ("alice".into(), vec![vec![0.1, 0.2, 0.3]]),
+            (
+                "bob".into(),
+                vec![vec![1.0, -1.0, 0.5], vec![0.9, -0.8, 0.4]],
+            ),
+        ]
+    }
+
+    fn encoded() -> Vec<u8> {
+        let speakers = speakers();
+        encode(
+            &model(),
+            speakers
+                .iter()
+                .map(|(name, embeddings)| (name.as_str(), embeddings.as_slice())),
+        )
+    }
+
+    fn error(bytes: &[u8], model: &ModelIdentity) -> SpeakerDbError {
+        decode(bytes, model)
+            .unwrap_err()
+            .downcast::<SpeakerDbError>()
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_eq!(decode(&encoded(), &model()).unwrap(), speakers());
+    }
+
+    #[test]
+    fn rejects_wrong_magic() {
+        let mut bytes = encoded();
+        bytes[0] = b'X';
+        assert!(matches!(
+            error(&bytes, &model()),
+            SpeakerDbError::InvalidFormat { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut bytes = encoded();
+        bytes[8..12].copy_from_slice(&(SPEAKER_DB_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            error(&bytes, &model()),
+            SpeakerDbError::UnsupportedVersion {
+                version: SPEAKER_DB_VERSION + 1
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_other_model() {
+        let other = ModelIdentity {
+            hash: 42,
+            ..model()
+        };
+        assert!(matches!(
+            error(&encoded(), &other),
+            SpeakerDbError::ModelMismatch { found, .. } if found == model()
+        ));
+    }
+
+    #[test]
+    fn rejects_other_dimension() {
+        let other = ModelIdentity {
+            dimension: 4,
+            ..model()
+        };
+        assert_eq!(
+            error(&encoded(), &other),
+            SpeakerDbError::DimensionMismatch {
+                expected: 4,
+                found: 3
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let bytes = encoded();
+        for len in [0, 10, bytes.len() - 1] {
+            assert!(matches!(
+                error(&bytes[..len], &model()),
+                SpeakerDbError::InvalidFormat { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_data() {
+        let mut bytes = encoded();
+        bytes.push(0);
+        assert!(matches!(
+            error(&bytes, &model()),
+            SpeakerDbError::InvalidFormat { .. }
+        ));
+    }
+}
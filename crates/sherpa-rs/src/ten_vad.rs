@@ -1,11 +1,18 @@
 use std::mem;
 
-use crate::{get_default_provider, utils::cstring_from_str};
+pub use crate::vad::SpeechSegment;
+use crate::{
+    get_default_provider,
+    utils::cstring_from_str,
+    vad::{self, VoiceActivityDetector},
+};
 use eyre::Result;
 
 #[derive(Debug)]
 pub struct TenVad {
     pub(crate) vad: *const sherpa_rs_sys::SherpaOnnxVoiceActivityDetector,
+    sample_rate: u32,
+    window_size: usize,
 }
 
 #[derive(Debug)]
@@ -39,12 +46,6 @@ impl Default for TenVadConfig {
     }
 }
 
-#[derive(Debug)]
-pub struct SpeechSegment {
-    pub start: i32,
-    pub samples: Vec<f32>,
-}
-
 impl TenVad {
     pub fn new(config: TenVadConfig, buffer_size_in_seconds: f32) -> Result<Self> {
         let provider = config.provider.unwrap_or(get_default_provider());
@@ -72,14 +73,13 @@ impl TenVad {
             }
         };
 
-        unsafe {
-            let vad = sherpa_rs_sys::SherpaOnnxCreateVoiceActivityDetector(
-                &vad_config,
-                buffer_size_in_seconds,
-            );
+        let vad = vad::create_raw(&vad_config, buffer_size_in_seconds)?;
 
-            Ok(Self { vad })
-        }
+        Ok(Self {
+            vad,
+            sample_rate: config.sample_rate,
+            window_size: config.window_size as usize,
+        })
     }
 
     pub fn is_empty(&mut self) -> bool {
@@ -87,22 +87,7 @@ impl TenVad {
     }
 
     pub fn front(&mut self) -> SpeechSegment {
-        unsafe {
-            let segment_ptr = sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorFront(self.vad);
-            let raw_segment = segment_ptr.read();
-            let samples: &[f32] =
-                std::slice::from_raw_parts(raw_segment.samples, raw_segment.n as usize);
-
-            let segment = SpeechSegment {
-                samples: samples.to_vec(),
-                start: raw_segment.start,
-            };
-
-            // Free
-            sherpa_rs_sys::SherpaOnnxDestroySpeechSegment(segment_ptr);
-
-            segment
-        }
+        unsafe { vad::front(self.vad) }
     }
 
     pub fn flush(&mut self) {
@@ -111,16 +96,8 @@ impl TenVad {
         }
     }
 
-    pub fn accept_waveform(&mut self, mut samples: Vec<f32>) {
-        let samples_ptr = samples.as_mut_ptr();
-        let samples_length = samples.len();
-        unsafe {
-            sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorAcceptWaveform(
-                self.vad,
-                samples_ptr,
-                samples_length.try_into().unwrap(),
-            );
-        };
+    pub fn accept_waveform(&mut self, samples: Vec<f32>) {
+        unsafe { vad::accept_waveform(self.vad, &samples) }
     }
 
     pub fn pop(&mut self) {
@@ -140,6 +117,44 @@ impl TenVad {
     }
 }
 
+impl VoiceActivityDetector for TenVad {
+    fn accept_waveform(&mut self, samples: Vec<f32>) {
+        TenVad::accept_waveform(self, samples)
+    }
+
+    fn is_empty(&mut self) -> bool {
+        TenVad::is_empty(self)
+    }
+
+    fn front(&mut self) -> SpeechSegment {
+        TenVad::front(self)
+    }
+
+    fn pop(&mut self) {
+        TenVad::pop(self)
+    }
+
+    fn flush(&mut self) {
+        TenVad::flush(self)
+    }
+
+    fn is_speech(&mut self) -> bool {
+        TenVad::is_speech(self)
+    }
+
+    fn clear(&mut self) {
+        TenVad::clear(self)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn window_size(&self) -> usize {
+        self.window_size
+    }
+}
+
 unsafe impl Send for TenVad {}
 unsafe impl Sync for TenVad {}
 
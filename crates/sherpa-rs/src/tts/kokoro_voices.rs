@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use eyre::{bail, Result};
+
+use super::{KokoroTts, KokoroTtsConfig};
+
+/// Max number of tokens a style vector is stored for
+pub const KOKORO_MAX_TOKENS: usize = 510;
+pub const KOKORO_STYLE_DIM: usize = 256;
+/// Number of floats of a single voice in voices.bin
+pub const KOKORO_VOICE_LEN: usize = KOKORO_MAX_TOKENS * KOKORO_STYLE_DIM;
+
+/// Style vectors of a Kokoro voices.bin, little endian f32 of shape `[num_voices, 510, 256]`.
+///
+/// The file doesn't store voice names (they're in the model metadata), so voices are
+/// named by their sid until [`KokoroVoices::set_name`] is used.
+///
+/// The model checks the number of voices in the file, so a blend replaces an existing
+/// voice with [`KokoroVoices::set_style`] instead of being appended.
+#[derive(Debug, Clone)]
+pub struct KokoroVoices {
+    names: Vec<String>,
+    styles: Vec<f32>,
+}
+
+impl KokoroVoices {
+    pub fn open(path: &str) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let voice_bytes = KOKORO_VOICE_LEN * 4;
+        if bytes.is_empty() || !bytes.len().is_multiple_of(voice_bytes) {
+            bail!(
+                "voices file size {} is not a multiple of {} bytes",
+                bytes.len(),
+                voice_bytes
+            );
+        }
+        let styles: Vec<f32> = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        let names = (0..bytes.len() / voice_bytes)
+            .map(|sid| sid.to_string())
+            .collect();
+        Ok(Self { names, styles })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Voice names in sid order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn set_name(&mut self, sid: i32, name: &str) -> Result<()> {
+        let index = self.index(sid)?;
+        self.names[index] = name.into();
+        Ok(())
+    }
+
+    /// Name all voices at once, eg. with the speaker names of the model
+    pub fn set_names(&mut self, names: Vec<String>) -> Result<()> {
+        if names.len() != self.len() {
+            bail!("expected {} names but got {}", self.len(), names.len());
+        }
+        self.names = names;
+        Ok(())
+    }
+
+    pub fn sid(&self, name: &str) -> Option<i32> {
+        self.names.iter().position(|n| n == name).map(|i| i as i32)
+    }
+
+    pub fn style(&self, sid: i32) -> Option<&[f32]> {
+        let index = self.index(sid).ok()?;
+        Some(&self.styles[index * KOKORO_VOICE_LEN..(index + 1) * KOKORO_VOICE_LEN])
+    }
+
+    pub fn set_style(&mut self, sid: i32, style: &[f32]) -> Result<()> {
+        let index = self.index(sid)?;
+        if style.len() != KOKORO_VOICE_LEN {
+            bail!(
+                "style has {} values but {} are expected",
+                style.len(),
+                KOKORO_VOICE_LEN
+            );
+        }
+        self.styles[index * KOKORO_VOICE_LEN..(index + 1) * KOKORO_VOICE_LEN]
+            .copy_from_slice(style);
+        Ok(())
+    }
+
+    /// Weighted sum of voices by name, eg. `[("af_bella", 0.7), ("am_adam", 0.3)]`.
+    ///
+    /// Weights are normalized to sum to 1.
+    pub fn blend(&self, weights: &[(&str, f32)]) -> Result<Vec<f32>> {
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        if weights.is_empty() || total <= 0.0 {
+            bail!("blend weights must sum to a positive value");
+        }
+        let mut style = vec![0.0; KOKORO_VOICE_LEN];
+        for &(name, weight) in weights {
+            let Some(voice) = self.sid(name).and_then(|sid| self.style(sid)) else {
+                bail!("unknown voice {}", name);
+            };
+            let weight = weight / total;
+            for (out, value) in style.iter_mut().zip(voice) {
+                *out += value * weight;
+            }
+        }
+        Ok(style)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.styles.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// Write a voices file usable as `KokoroTtsConfig.voices`
+    pub fn write(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    fn index(&self, sid: i32) -> Result<usize> {
+        if sid < 0 || sid as usize >= self.len() {
+            bail!("sid {} is out of range (0..{})", sid, self.len());
+        }
+        Ok(sid as usize)
+    }
+}
+
+impl KokoroTts {
+    /// Create the TTS with `voices` instead of the file in `config.voices`.
+    ///
+    /// The voices are loaded through a temporary file that's removed once the model is created.
+    pub fn with_voices(mut config: KokoroTtsConfig, voices: &KokoroVoices) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "sherpa-rs-kokoro-voices-{}-{}.bin",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path = path.to_string_lossy().to_string();
+        voices.write(&path)?;
+
+        config.voices = path.clone();
+        let tts = KokoroTts::new(config);
+        if let Err(err) = std::fs::remove_file(&path) {
+            tracing::warn!("failed to remove {}: {}", path, err);
+        }
+        Ok(tts)
+    }
+}
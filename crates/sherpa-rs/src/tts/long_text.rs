@@ -0,0 +1,413 @@
+use eyre::{bail, Result};
+
+use super::{TextToSpeech, TtsAudio};
+
+const SENTENCE_ENDS: &[char] = &[
+    '.', '!', '?', ';', '…', '。', '！', '？', '；', '．', '।', '॥', '؟', '۔',
+];
+const CLAUSE_ENDS: &[char] = &[',', ':', '，', '、', '：', '،', '—'];
+const CLOSING: &[char] = &['"', '\'', ')', ']', '”', '’', '»', '」', '』', '）'];
+/// Words ending with a period that usually don't end a sentence
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "approx", "fig",
+];
+
+#[derive(Debug, Clone)]
+pub struct LongTextOptions {
+    /// Chunks longer than this many characters are split at clauses, then words
+    pub max_chars: usize,
+    pub sid: i32,
+    pub speed: f32,
+    /// Silence in seconds after a chunk that was cut inside a sentence
+    pub clause_pause: f32,
+    pub sentence_pause: f32,
+    /// Silence in seconds after a paragraph (blank line)
+    pub paragraph_pause: f32,
+}
+
+impl Default for LongTextOptions {
+    fn default() -> Self {
+        Self {
+            max_chars: 300,
+            sid: 0,
+            speed: 1.0,
+            clause_pause: 0.1,
+            sentence_pause: 0.3,
+            paragraph_pause: 0.7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextChunk {
+    pub text: String,
+    /// Silence in seconds to add after the chunk
+    pub pause: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LongTextProgress<'a> {
+    /// Index of the chunk that was just synthesized
+    pub index: usize,
+    pub num_chunks: usize,
+    pub text: &'a str,
+}
+
+impl LongTextProgress<'_> {
+    /// Progress in range [0, 1]
+    pub fn fraction(&self) -> f32 {
+        (self.index + 1) as f32 / self.num_chunks as f32
+    }
+}
+
+/// Split text into chunks of at most `options.max_chars` characters.
+///
+/// Sentences end at Latin, CJK, Devanagari and Arabic terminators and at line breaks.
+/// A period only ends a sentence before whitespace, so decimals and common
+/// abbreviations stay intact. Long sentences are cut at clause punctuation, then at
+/// whitespace, and as a last resort anywhere (eg. CJK text without punctuation).
+pub fn split_text(text: &str, options: &LongTextOptions) -> Vec<TextChunk> {
+    let max_chars = options.max_chars.max(1);
+    let mut chunks = Vec::new();
+
+    for paragraph in split_paragraphs(text) {
+        for line in paragraph.lines() {
+            for sentence in split_line(line) {
+                for piece in split_long(sentence, max_chars) {
+                    chunks.push(TextChunk {
+                        text: piece.to_string(),
+                        pause: options.clause_pause,
+                    });
+                }
+                if let Some(last) = chunks.last_mut() {
+                    last.pause = options.sentence_pause;
+                }
+            }
+        }
+        if let Some(last) = chunks.last_mut() {
+            last.pause = options.paragraph_pause;
+        }
+    }
+    if let Some(last) = chunks.last_mut() {
+        last.pause = 0.0;
+    }
+    chunks
+}
+
+fn split_paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if !text[start..offset].trim().is_empty() {
+                paragraphs.push(&text[start..offset]);
+            }
+            start = offset + line.len();
+        }
+        offset += line.len();
+    }
+    if !text[start..].trim().is_empty() {
+        paragraphs.push(&text[start..]);
+    }
+    paragraphs
+}
+
+fn is_initial(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if c.is_uppercase())
+}
+
+/// Whether the period between `before` and `after` follows an abbreviation
+fn is_abbreviation(before: &str, after: &str) -> bool {
+    let mut words = before
+        .rsplit(|c: char| c.is_whitespace() || CLOSING.contains(&c) || c == '(')
+        .filter(|word| !word.is_empty());
+    let word = words.next().unwrap_or("");
+    // Single letter initials like "J. R. R. Tolkien". "I." and "A." usually end a
+    // sentence, so they're only initials next to another one.
+    if is_initial(word) {
+        if !matches!(word, "I" | "A") {
+            return true;
+        }
+        let previous = words.next().unwrap_or("");
+        let next = after.split_whitespace().next().unwrap_or("");
+        return [previous, next]
+            .iter()
+            .any(|word| word.strip_suffix('.').is_some_and(is_initial));
+    }
+    ABBREVIATIONS
+        .iter()
+        .any(|abbreviation| word.eq_ignore_ascii_case(abbreviation))
+}
+
+/// Split a line into sentences, keeping their terminators. Shared by the streaming
+/// synthesis of the models.
+pub(crate) fn split_line(line: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if !SENTENCE_ENDS.contains(&c) {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        // Keep "?!", "..." and closing quotes with the sentence
+        while let Some(&(j, next)) = chars.peek() {
+            if SENTENCE_ENDS.contains(&next) || CLOSING.contains(&next) {
+                end = j + next.len_utf8();
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if c == '.' {
+            let followed_by_space = match chars.peek() {
+                Some(&(_, next)) => next.is_whitespace(),
+                None => true,
+            };
+            if !followed_by_space
+                || (end == i + 1 && is_abbreviation(&line[start..i], &line[end..]))
+            {
+                continue;
+            }
+        }
+        push_trimmed(&mut sentences, &line[start..end]);
+        start = end;
+    }
+    push_trimmed(&mut sentences, &line[start..]);
+    sentences
+}
+
+fn push_trimmed<'a>(pieces: &mut Vec<&'a str>, piece: &'a str) {
+    let piece = piece.trim();
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+}
+
+/// Byte offset after `n` characters
+fn char_offset(text: &str, n: usize) -> usize {
+    text.char_indices().nth(n).map_or(text.len(), |(i, _)| i)
+}
+
+/// Split at the last position before `max_chars` where `is_break` matches, keeping
+/// the matching character in the first part.
+fn split_at_last(text: &str, max_chars: usize, is_break: impl Fn(char) -> bool) -> Option<usize> {
+    let limit = char_offset(text, max_chars);
+    text[..limit]
+        .char_indices()
+        .filter(|&(i, c)| i > 0 && is_break(c))
+        .map(|(i, c)| i + c.len_utf8())
+        .last()
+}
+
+fn split_long(sentence: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = sentence;
+    while rest.chars().count() > max_chars {
+        let end = split_at_last(rest, max_chars, |c| CLAUSE_ENDS.contains(&c))
+            .or_else(|| split_at_last(rest, max_chars, char::is_whitespace))
+            .unwrap_or_else(|| char_offset(rest, max_chars));
+        push_trimmed(&mut pieces, &rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    push_trimmed(&mut pieces, rest);
+    pieces
+}
+
+/// Synthesize long text chunk by chunk, so memory per model call stays bounded.
+///
+/// `callback` receives the progress and the audio of each chunk, including the pause
+/// after it. Return `false` to stop. Use it with an [`crate::audio_writer::AudioWriter`]
+/// to avoid keeping the whole audio in memory.
+pub fn synthesize_long_text_with_callback(
+    tts: &mut dyn TextToSpeech,
+    text: &str,
+    options: &LongTextOptions,
+    callback: &mut dyn FnMut(&LongTextProgress, &[f32]) -> bool,
+) -> Result<()> {
+    let chunks = split_text(text, options);
+    if chunks.is_empty() {
+        bail!("text is empty");
+    }
+    let sample_rate = tts.sample_rate();
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let mut samples = tts
+            .synthesize(&chunk.text, options.sid, options.speed)?
+            .samples;
+        let pause = (chunk.pause * sample_rate as f32) as usize;
+        samples.resize(samples.len() + pause, 0.0);
+
+        let progress = LongTextProgress {
+            index,
+            num_chunks: chunks.len(),
+            text: &chunk.text,
+        };
+        if !callback(&progress, &samples) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Synthesize long text chunk by chunk and join the audio.
+pub fn synthesize_long_text(
+    tts: &mut dyn TextToSpeech,
+    text: &str,
+    options: &LongTextOptions,
+) -> Result<TtsAudio> {
+    let sample_rate = tts.sample_rate();
+    let mut samples = Vec::new();
+    synthesize_long_text_with_callback(tts, text, options, &mut |_, chunk| {
+        samples.extend_from_slice(chunk);
+        true
+    })?;
+    let duration = (samples.len() as u32 / sample_rate.max(1)) as i32;
+
+    Ok(TtsAudio {
+        samples,
+        sample_rate,
+        duration,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(text: &str, max_chars: usize) -> Vec<String> {
+        let options = LongTextOptions {
+            max_chars,
+            ..Default::default()
+        };
+        split_text(text, &options)
+            .into_iter()
+            .map(|chunk| chunk.text)
+            .collect()
+    }
+
+    #[test]
+    fn splits_sentences() {
+        assert_eq!(
+            chunks("First one. Second one! Third one? Fourth; fifth", 300),
+            [
+                "First one.",
+                "Second one!",
+                "Third one?",
+                "Fourth;",
+                "fifth"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_decimals() {
+        assert_eq!(
+            chunks("It costs 3.50 dollars. Pi is 3.14159.", 300),
+            ["It costs 3.50 dollars.", "Pi is 3.14159."]
+        );
+    }
+
+    #[test]
+    fn keeps_abbreviations() {
+        assert_eq!(
+            chunks(
+                "Dr. Smith met Mrs. Jones, e.g. at noon. Then they left.",
+                300
+            ),
+            ["Dr. Smith met Mrs. Jones, e.g. at noon.", "Then they left."]
+        );
+    }
+
+    #[test]
+    fn keeps_initials() {
+        assert_eq!(
+            chunks("J. R. R. Tolkien wrote it. I. A. Richards read it.", 300),
+            ["J. R. R. Tolkien wrote it.", "I. A. Richards read it."]
+        );
+    }
+
+    #[test]
+    fn single_letter_words_end_sentences() {
+        assert_eq!(
+            chunks("So do I. Then we go.", 300),
+            ["So do I.", "Then we go."]
+        );
+        assert_eq!(
+            chunks("I got an A. It was hard.", 300),
+            ["I got an A.", "It was hard."]
+        );
+    }
+
+    #[test]
+    fn keeps_closing_quotes_and_repeated_terminators() {
+        assert_eq!(
+            chunks("He said \"Stop!\" Then he left. Really?! Yes... Fine.", 300),
+            [
+                "He said \"Stop!\"",
+                "Then he left.",
+                "Really?!",
+                "Yes...",
+                "Fine."
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_cjk_without_spaces() {
+        assert_eq!(
+            chunks("你好。今天天气很好！我们走吧", 300),
+            ["你好。", "今天天气很好！", "我们走吧"]
+        );
+    }
+
+    #[test]
+    fn splits_long_sentences() {
+        // At clause punctuation first, then at whitespace, then anywhere
+        assert_eq!(
+            chunks("alpha beta, gamma delta epsilon", 20),
+            ["alpha beta,", "gamma delta epsilon"]
+        );
+        assert_eq!(
+            chunks("one two three four five six seven", 20),
+            ["one two three four", "five six seven"]
+        );
+        assert_eq!(
+            chunks("一二三四五六七八九十", 4),
+            ["一二三四", "五六七八", "九十"]
+        );
+    }
+
+    #[test]
+    fn pauses_after_chunks() {
+        let options = LongTextOptions {
+            max_chars: 12,
+            ..Default::default()
+        };
+        let pauses: Vec<(String, f32)> = split_text("One. Two three four five\n\nSix", &options)
+            .into_iter()
+            .map(|chunk| (chunk.text, chunk.pause))
+            .collect();
+        assert_eq!(
+            pauses,
+            [
+                ("One.".to_string(), options.sentence_pause),
+                ("Two three".to_string(), options.clause_pause),
+                ("four five".to_string(), options.paragraph_pause),
+                ("Six".to_string(), 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(
+            chunks("no period\nnext line", 300),
+            ["no period", "next line"]
+        );
+        assert!(chunks(" \n\n ", 300).is_empty());
+    }
+}
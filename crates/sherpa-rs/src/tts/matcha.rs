@@ -4,7 +4,7 @@ use crate::{utils::cstring_from_str, OnnxConfig};
 use eyre::Result;
 use sherpa_rs_sys;
 
-use super::{CommonTtsConfig, TtsAudio};
+use super::{CommonTtsConfig, TextToSpeech, TtsAudio};
 
 pub struct MatchaTts {
     tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts,
@@ -75,9 +75,54 @@ impl MatchaTts {
         Self { tts }
     }
 
+    /// Sample rate of the generated audio
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { super::sample_rate(self.tts) }
+    }
+
+    pub fn num_speakers(&self) -> i32 {
+        unsafe { super::num_speakers(self.tts) }
+    }
+
     pub fn create(&mut self, text: &str, sid: i32, speed: f32) -> Result<TtsAudio> {
         unsafe { super::create(self.tts, text, sid, speed) }
     }
+
+    /// Like `create` but `callback` receives the audio of each sentence as it's generated.
+    /// Return `false` from the callback to stop.
+    pub fn create_with_callback<F: FnMut(&[f32], f32) -> bool>(
+        &mut self,
+        text: &str,
+        sid: i32,
+        speed: f32,
+        callback: F,
+    ) -> Result<TtsAudio> {
+        unsafe { super::create_with_callback(self.tts, text, sid, speed, callback) }
+    }
+}
+
+impl TextToSpeech for MatchaTts {
+    fn synthesize(&mut self, text: &str, sid: i32, speed: f32) -> Result<TtsAudio> {
+        self.create(text, sid, speed)
+    }
+
+    fn synthesize_with_callback(
+        &mut self,
+        text: &str,
+        sid: i32,
+        speed: f32,
+        callback: &mut dyn FnMut(&[f32], f32) -> bool,
+    ) -> Result<TtsAudio> {
+        self.create_with_callback(text, sid, speed, callback)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        MatchaTts::sample_rate(self)
+    }
+
+    fn num_speakers(&self) -> i32 {
+        MatchaTts::num_speakers(self)
+    }
 }
 
 unsafe impl Send for MatchaTts {}
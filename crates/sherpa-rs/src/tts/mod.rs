@@ -1,6 +1,10 @@
 mod kitten;
 mod kokoro;
+mod kokoro_voices;
+mod long_text;
 mod matcha;
+mod postprocess;
+mod ssml;
 mod vits;
 mod zipvoice;
 
@@ -10,11 +14,21 @@ use eyre::{bail, Result};
 
 pub use kitten::{KittenTts, KittenTtsConfig};
 pub use kokoro::{KokoroTts, KokoroTtsConfig};
+pub use kokoro_voices::{KokoroVoices, KOKORO_MAX_TOKENS, KOKORO_STYLE_DIM, KOKORO_VOICE_LEN};
+pub use long_text::{
+    split_text, synthesize_long_text, synthesize_long_text_with_callback, LongTextOptions,
+    LongTextProgress, TextChunk,
+};
 pub use matcha::{MatchaTts, MatchaTtsConfig};
+pub use postprocess::{Loudness, PostProcessOptions};
+pub use ssml::{parse_ssml, synthesize_ssml, SsmlOptions, SsmlPart};
 pub use vits::{VitsTts, VitsTtsConfig};
-pub use zipvoice::{ZipVoiceTts, ZipVoiceTtsConfig};
+pub use zipvoice::{ZipVoicePrompt, ZipVoicePromptStore, ZipVoiceTts, ZipVoiceTtsConfig};
 
-use crate::utils::cstring_from_str;
+use crate::{
+    g711::{self, G711Law},
+    utils::cstring_from_str,
+};
 
 #[derive(Debug)]
 pub struct TtsAudio {
@@ -23,6 +37,63 @@ pub struct TtsAudio {
     pub duration: i32,
 }
 
+impl TtsAudio {
+    /// Duration in seconds
+    pub fn duration_secs(&self) -> f32 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    /// Resample to 8kHz and encode as G.711 for telephony.
+    pub fn to_g711(&self, law: G711Law) -> Result<Vec<u8>> {
+        g711::encode_from(&self.samples, self.sample_rate, law)
+    }
+}
+
+/// Common interface implemented by every TTS engine.
+pub trait TextToSpeech: Send {
+    fn synthesize(&mut self, text: &str, sid: i32, speed: f32) -> Result<TtsAudio>;
+
+    /// Like `synthesize` but `callback` receives the audio of each sentence as it's
+    /// generated, with the progress in range [0, 1]. Return `false` to stop.
+    fn synthesize_with_callback(
+        &mut self,
+        text: &str,
+        sid: i32,
+        speed: f32,
+        callback: &mut dyn FnMut(&[f32], f32) -> bool,
+    ) -> Result<TtsAudio>;
+
+    fn sample_rate(&self) -> u32;
+
+    fn num_speakers(&self) -> i32;
+}
+
+pub enum TtsConfig {
+    Kokoro(KokoroTtsConfig),
+    Vits(VitsTtsConfig),
+    Matcha(MatchaTtsConfig),
+    Kitten(KittenTtsConfig),
+    ZipVoice(ZipVoiceTtsConfig, ZipVoicePrompt),
+}
+
+/// Create a TTS engine from config so the engine can be picked at runtime.
+pub fn from_config(config: TtsConfig) -> Box<dyn TextToSpeech> {
+    match config {
+        TtsConfig::Kokoro(config) => Box::new(KokoroTts::new(config)),
+        TtsConfig::Vits(config) => Box::new(VitsTts::new(config)),
+        TtsConfig::Matcha(config) => Box::new(MatchaTts::new(config)),
+        TtsConfig::Kitten(config) => Box::new(KittenTts::new(config)),
+        TtsConfig::ZipVoice(config, prompt) => {
+            let mut tts = ZipVoiceTts::new(config);
+            tts.set_prompt(prompt);
+            Box::new(tts)
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct CommonTtsConfig {
     pub rule_fars: String,
@@ -70,7 +141,75 @@ pub unsafe fn create(
 ) -> Result<TtsAudio> {
     let text = cstring_from_str(text);
     let audio_ptr = sherpa_rs_sys::SherpaOnnxOfflineTtsGenerate(tts, text.as_ptr(), sid, speed);
+    read_generated_audio(audio_ptr)
+}
 
+/// # Safety
+///
+/// This function dereference sherpa_rs_sys::SherpaOnnxOfflineTts
+pub(crate) unsafe fn sample_rate(tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts) -> u32 {
+    sherpa_rs_sys::SherpaOnnxOfflineTtsSampleRate(tts) as u32
+}
+
+/// # Safety
+///
+/// This function dereference sherpa_rs_sys::SherpaOnnxOfflineTts
+pub(crate) unsafe fn num_speakers(tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts) -> i32 {
+    sherpa_rs_sys::SherpaOnnxOfflineTtsNumSpeakers(tts)
+}
+
+type ChunkCallback<'a> = dyn FnMut(&[f32], f32) -> bool + 'a;
+
+/// Generate speech and call `callback` with the samples of every sentence as soon as
+/// it's ready, along with the progress in range [0, 1]. Return `false` from the
+/// callback to stop early. The returned audio contains everything generated.
+///
+/// # Safety
+///
+/// This function dereference sherpa_rs_sys::SherpaOnnxOfflineTts
+pub unsafe fn create_with_callback<F: FnMut(&[f32], f32) -> bool>(
+    tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts,
+    text: &str,
+    sid: i32,
+    speed: f32,
+    mut callback: F,
+) -> Result<TtsAudio> {
+    let text = cstring_from_str(text);
+    let mut callback: &mut ChunkCallback = &mut callback;
+    let callback_ptr = &mut callback as *mut &mut ChunkCallback as *mut std::ffi::c_void;
+
+    let audio_ptr = sherpa_rs_sys::SherpaOnnxOfflineTtsGenerateWithProgressCallbackWithArg(
+        tts,
+        text.as_ptr(),
+        sid,
+        speed,
+        Some(chunk_callback_wrapper),
+        callback_ptr,
+    );
+    read_generated_audio(audio_ptr)
+}
+
+unsafe extern "C" fn chunk_callback_wrapper(
+    samples: *const f32,
+    n: i32,
+    progress: f32,
+    arg: *mut std::ffi::c_void,
+) -> i32 {
+    let callback = &mut *(arg as *mut &mut ChunkCallback);
+    let samples = if samples.is_null() || n <= 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(samples, n as usize)
+    };
+    callback(samples, progress).into()
+}
+
+/// # Safety
+///
+/// `audio_ptr` must be null or returned by one of the SherpaOnnxOfflineTtsGenerate functions
+pub(crate) unsafe fn read_generated_audio(
+    audio_ptr: *const sherpa_rs_sys::SherpaOnnxGeneratedAudio,
+) -> Result<TtsAudio> {
     if audio_ptr.is_null() {
         bail!("audio is null");
     }
@@ -0,0 +1,264 @@
+use std::{collections::VecDeque, f64::consts::PI};
+
+use super::TtsAudio;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loudness {
+    /// Integrated loudness (EBU R128 / ITU-R BS.1770) in LUFS
+    Lufs(f32),
+    /// RMS level in dBFS
+    Rms(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct PostProcessOptions {
+    /// Remove leading and trailing audio quieter than this level in dBFS
+    pub trim_threshold_db: Option<f32>,
+    /// Silence in seconds kept around the speech when trimming
+    pub trim_padding: f32,
+    pub loudness: Option<Loudness>,
+    /// Maximum peak level in dBFS
+    pub peak_limit_db: Option<f32>,
+    /// Fade in duration in seconds
+    pub fade_in: f32,
+    /// Fade out duration in seconds
+    pub fade_out: f32,
+}
+
+impl Default for PostProcessOptions {
+    fn default() -> Self {
+        Self {
+            trim_threshold_db: Some(-50.0),
+            trim_padding: 0.05,
+            loudness: Some(Loudness::Lufs(-16.0)),
+            peak_limit_db: Some(-1.0),
+            fade_in: 0.005,
+            fade_out: 0.01,
+        }
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+impl TtsAudio {
+    /// Apply trimming, loudness normalization, peak limiting and fades in that order.
+    pub fn post_process(&mut self, options: &PostProcessOptions) {
+        if let Some(threshold_db) = options.trim_threshold_db {
+            self.trim_silence(threshold_db, options.trim_padding);
+        }
+        if let Some(loudness) = options.loudness {
+            self.normalize_loudness(loudness);
+        }
+        if let Some(ceiling_db) = options.peak_limit_db {
+            self.limit_peaks(ceiling_db);
+        }
+        self.fade(options.fade_in, options.fade_out);
+    }
+
+    /// Integrated loudness in LUFS, `None` for silence.
+    pub fn loudness_lufs(&self) -> Option<f32> {
+        integrated_loudness(&self.samples, self.sample_rate)
+    }
+
+    /// RMS level in dBFS, `None` for silence.
+    pub fn rms_db(&self) -> Option<f32> {
+        let sum: f64 = self.samples.iter().map(|&s| (s as f64).powi(2)).sum();
+        let mean = sum / self.samples.len().max(1) as f64;
+        (mean > 0.0).then(|| (10.0 * mean.log10()) as f32)
+    }
+
+    /// Apply gain so the audio reaches `target`. Silence is left unchanged.
+    pub fn normalize_loudness(&mut self, target: Loudness) {
+        let (current, target) = match target {
+            Loudness::Lufs(target) => (self.loudness_lufs(), target),
+            Loudness::Rms(target) => (self.rms_db(), target),
+        };
+        if let Some(current) = current {
+            self.apply_gain_db(target - current);
+        }
+    }
+
+    pub fn apply_gain_db(&mut self, db: f32) {
+        let gain = db_to_gain(db);
+        for sample in &mut self.samples {
+            *sample *= gain;
+        }
+    }
+
+    /// Remove leading and trailing 10ms frames below `threshold_db`, keeping
+    /// `padding` seconds around the rest. Audio that's entirely below the
+    /// threshold is left unchanged.
+    pub fn trim_silence(&mut self, threshold_db: f32, padding: f32) {
+        let frame = (self.sample_rate as usize / 100).max(1);
+        let threshold = db_to_gain(threshold_db).powi(2);
+        let loud = |chunk: &[f32]| {
+            chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32 > threshold
+        };
+
+        let frames: Vec<bool> = self.samples.chunks(frame).map(loud).collect();
+        let (Some(first), Some(last)) = (
+            frames.iter().position(|&l| l),
+            frames.iter().rposition(|&l| l),
+        ) else {
+            return;
+        };
+        let padding = (padding.max(0.0) * self.sample_rate as f32) as usize;
+        let start = (first * frame).saturating_sub(padding);
+        let end = ((last + 1) * frame + padding).min(self.samples.len());
+
+        self.samples.truncate(end);
+        self.samples.drain(..start);
+        self.update_duration();
+    }
+
+    /// Brickwall limiter keeping peaks under `ceiling_db`, with 5ms lookahead
+    /// and 50ms release so the gain changes smoothly.
+    pub fn limit_peaks(&mut self, ceiling_db: f32) {
+        let ceiling = db_to_gain(ceiling_db);
+        let lookahead = (self.sample_rate as usize * 5 / 1000).max(1);
+        let release = 1.0 - (-1.0 / (0.05 * self.sample_rate.max(1) as f32)).exp();
+        if self.samples.iter().all(|s| s.abs() <= ceiling) {
+            return;
+        }
+
+        let gains: Vec<f32> = self
+            .samples
+            .iter()
+            .map(|s| (ceiling / s.abs()).min(1.0))
+            .collect();
+        // Minimum gain over the next `lookahead` samples, then averaged over the
+        // previous `lookahead` samples. Each averaged value only includes windows
+        // covering the current sample, so the peak stays under the ceiling.
+        let ahead = sliding_min(&gains, lookahead);
+        let mut sum = 0.0;
+        let mut envelope = 1.0f32;
+        for i in 0..self.samples.len() {
+            sum += ahead[i];
+            if i >= lookahead {
+                sum -= ahead[i - lookahead];
+            }
+            let smoothed = sum / lookahead.min(i + 1) as f32;
+            envelope = smoothed.min(envelope + (1.0 - envelope) * release);
+            self.samples[i] *= envelope;
+        }
+    }
+
+    /// Linear fade in and out, durations in seconds.
+    pub fn fade(&mut self, fade_in: f32, fade_out: f32) {
+        let len = self.samples.len();
+        let fade_in = ((fade_in.max(0.0) * self.sample_rate as f32) as usize).min(len / 2);
+        let fade_out = ((fade_out.max(0.0) * self.sample_rate as f32) as usize).min(len / 2);
+
+        for i in 0..fade_in {
+            self.samples[i] *= i as f32 / fade_in as f32;
+        }
+        for i in 0..fade_out {
+            self.samples[len - 1 - i] *= i as f32 / fade_out as f32;
+        }
+    }
+
+    fn update_duration(&mut self) {
+        self.duration = self.duration_secs() as i32;
+    }
+}
+
+/// Minimum of `values[i..i + window]` for every `i`
+fn sliding_min(values: &[f32], window: usize) -> Vec<f32> {
+    let mut result = vec![0.0; values.len()];
+    let mut deque: VecDeque<usize> = VecDeque::new();
+    for i in (0..values.len()).rev() {
+        while deque.back().is_some_and(|&j| values[j] >= values[i]) {
+            deque.pop_back();
+        }
+        deque.push_back(i);
+        while deque.front().is_some_and(|&j| j >= i + window) {
+            deque.pop_front();
+        }
+        result[i] = values[*deque.front().unwrap()];
+    }
+    result
+}
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[1] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[2] * y;
+        y
+    }
+}
+
+/// K-weighting filter of BS.1770 for any sample rate (as in libebur128)
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let g = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+/// Gated integrated loudness of mono audio
+fn integrated_loudness(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    if samples.is_empty() || sample_rate == 0 {
+        return None;
+    }
+    let [mut shelf, mut high_pass] = k_weighting(sample_rate);
+    let weighted: Vec<f64> = samples
+        .iter()
+        .map(|&s| high_pass.process(shelf.process(s as f64)).powi(2))
+        .collect();
+
+    // 400ms blocks with 75% overlap, or a single block for shorter clips
+    let block = (sample_rate as usize * 4 / 10).min(weighted.len());
+    let hop = (block / 4).max(1);
+    let blocks: Vec<f64> = (0..=(weighted.len() - block) / hop)
+        .map(|i| weighted[i * hop..i * hop + block].iter().sum::<f64>() / block as f64)
+        .collect();
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let gated_mean = |threshold: f64| {
+        let gated: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&power| power > 0.0 && loudness(power) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let absolute = gated_mean(-70.0)?;
+    let relative = gated_mean(loudness(absolute) - 10.0)?;
+    Some(loudness(relative) as f32)
+}
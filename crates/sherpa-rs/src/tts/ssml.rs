@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+
+use eyre::{bail, Result};
+
+use super::{TextToSpeech, TtsAudio};
+
+#[derive(Debug, Clone)]
+pub struct SsmlOptions {
+    /// Voice names usable in `<voice name="...">` mapped to speaker ids
+    pub voices: HashMap<String, i32>,
+    pub sid: i32,
+    pub speed: f32,
+    /// Silence in seconds added after `</s>`
+    pub sentence_pause: f32,
+    /// Silence in seconds added after `</p>`
+    pub paragraph_pause: f32,
+}
+
+impl Default for SsmlOptions {
+    fn default() -> Self {
+        Self {
+            voices: HashMap::new(),
+            sid: 0,
+            speed: 1.0,
+            sentence_pause: 0.2,
+            paragraph_pause: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SsmlPart {
+    Text {
+        text: String,
+        sid: i32,
+        speed: f32,
+    },
+    /// Silence in seconds
+    Break(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SayAs {
+    Normal,
+    Characters,
+}
+
+#[derive(Debug, Clone)]
+struct Context {
+    name: String,
+    sid: i32,
+    speed: f32,
+    say_as: SayAs,
+}
+
+struct Parser<'a> {
+    options: &'a SsmlOptions,
+    stack: Vec<Context>,
+    parts: Vec<SsmlPart>,
+    text: String,
+}
+
+/// Parse the supported SSML subset: `<speak>`, `<break>`, `<prosody rate>`,
+/// `<voice name|sid>`, `<say-as interpret-as="characters|digits">`, `<s>` and `<p>`.
+/// Other elements are ignored but their text is kept.
+pub fn parse_ssml(ssml: &str, options: &SsmlOptions) -> Result<Vec<SsmlPart>> {
+    let mut parser = Parser {
+        options,
+        stack: vec![Context {
+            name: String::new(),
+            sid: options.sid,
+            speed: options.speed,
+            say_as: SayAs::Normal,
+        }],
+        parts: Vec::new(),
+        text: String::new(),
+    };
+
+    let mut rest = ssml;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let Some(end) = comment.find("-->") else {
+                bail!("unterminated comment");
+            };
+            rest = &comment[end + 3..];
+        } else if let Some(declaration) = rest.strip_prefix("<?") {
+            let Some(end) = declaration.find("?>") else {
+                bail!("unterminated declaration");
+            };
+            rest = &declaration[end + 2..];
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let Some(end) = tag.find('>') else {
+                bail!("unterminated tag");
+            };
+            parser.tag(&tag[..end])?;
+            rest = &tag[end + 1..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            parser.push_text(&unescape(&rest[..end])?);
+            rest = &rest[end..];
+        }
+    }
+    if parser.stack.len() > 1 {
+        bail!("unclosed element <{}>", parser.stack.last().unwrap().name);
+    }
+    parser.flush();
+    Ok(parser.parts)
+}
+
+impl Parser<'_> {
+    fn current(&self) -> &Context {
+        self.stack.last().unwrap()
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.current().say_as == SayAs::Characters {
+            for c in text.chars().filter(|c| !c.is_whitespace()) {
+                self.text.push(c);
+                self.text.push(' ');
+            }
+        } else {
+            self.text.push_str(text);
+        }
+    }
+
+    fn flush(&mut self) {
+        let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.text.clear();
+        if text.is_empty() {
+            return;
+        }
+        let (sid, speed) = (self.current().sid, self.current().speed);
+        // Keep text in one piece when only say-as or an unknown element changed
+        match self.parts.last_mut() {
+            Some(SsmlPart::Text {
+                text: previous,
+                sid: previous_sid,
+                speed: previous_speed,
+            }) if *previous_sid == sid && *previous_speed == speed => {
+                previous.push(' ');
+                previous.push_str(&text);
+            }
+            _ => self.parts.push(SsmlPart::Text { text, sid, speed }),
+        }
+    }
+
+    fn add_break(&mut self, seconds: f32) {
+        if seconds <= 0.0 {
+            return;
+        }
+        self.flush();
+        match self.parts.last_mut() {
+            Some(SsmlPart::Break(previous)) => *previous += seconds,
+            _ => self.parts.push(SsmlPart::Break(seconds)),
+        }
+    }
+
+    fn tag(&mut self, tag: &str) -> Result<()> {
+        if let Some(name) = tag.strip_prefix('/') {
+            return self.end_tag(name.trim());
+        }
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (name, attributes) = split_tag(tag)?;
+
+        let mut context = self.current().clone();
+        context.name = name.to_string();
+        match name {
+            "break" => {
+                let seconds = match (attributes.get("time"), attributes.get("strength")) {
+                    (Some(time), _) => parse_time(time)?,
+                    (None, Some(strength)) => parse_strength(strength)?,
+                    (None, None) => 0.5,
+                };
+                self.add_break(seconds);
+            }
+            "prosody" => {
+                if let Some(rate) = attributes.get("rate") {
+                    context.speed *= parse_rate(rate)?;
+                }
+            }
+            "voice" => {
+                if let Some(sid) = attributes.get("sid") {
+                    context.sid = sid.parse()?;
+                } else if let Some(name) = attributes.get("name") {
+                    context.sid = match self.options.voices.get(name.as_str()) {
+                        Some(&sid) => sid,
+                        None => match name.parse() {
+                            Ok(sid) => sid,
+                            Err(_) => bail!("unknown voice {}", name),
+                        },
+                    };
+                }
+            }
+            "say-as" => {
+                let interpret_as = attributes.get("interpret-as").map(String::as_str);
+                if matches!(
+                    interpret_as,
+                    Some("characters" | "spell-out" | "digits" | "verbatim")
+                ) {
+                    context.say_as = SayAs::Characters;
+                }
+            }
+            "s" | "p" => {}
+            _ => tracing::debug!("ignoring unsupported ssml element <{}>", name),
+        }
+
+        if name != "break" && !self_closing {
+            self.flush();
+            self.stack.push(context);
+        }
+        Ok(())
+    }
+
+    fn end_tag(&mut self, name: &str) -> Result<()> {
+        if self.stack.len() <= 1 || self.current().name != name {
+            bail!("unexpected closing element </{}>", name);
+        }
+        self.flush();
+        self.stack.pop();
+        match name {
+            "s" => self.add_break(self.options.sentence_pause),
+            "p" => self.add_break(self.options.paragraph_pause),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn split_tag(tag: &str) -> Result<(&str, HashMap<String, String>)> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        bail!("empty element name");
+    }
+
+    let mut attributes = HashMap::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let Some(eq) = rest.find('=') else {
+            bail!("invalid attributes in <{}>", name);
+        };
+        let key = rest[..eq].trim().to_string();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            bail!("attribute {} in <{}> must be quoted", key, name);
+        };
+        let Some(end) = value[1..].find(quote) else {
+            bail!("unterminated attribute {} in <{}>", key, name);
+        };
+        attributes.insert(key, unescape(&value[1..1 + end])?);
+        rest = value[end + 2..].trim_start();
+    }
+    Ok((name, attributes))
+}
+
+fn unescape(text: &str) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find(';') else {
+            bail!("unterminated entity");
+        };
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else {
+                    entity.strip_prefix('#').and_then(|dec| dec.parse().ok())
+                };
+                match code.and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => bail!("unknown entity &{};", entity),
+                }
+            }
+        };
+        result.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Parse `500ms` or `1.5s` to seconds
+fn parse_time(time: &str) -> Result<f32> {
+    let time = time.trim();
+    let seconds = if let Some(ms) = time.strip_suffix("ms") {
+        ms.trim().parse::<f32>()? / 1000.0
+    } else if let Some(s) = time.strip_suffix('s') {
+        s.trim().parse::<f32>()?
+    } else {
+        bail!("invalid break time {}", time);
+    };
+    Ok(seconds.max(0.0))
+}
+
+fn parse_strength(strength: &str) -> Result<f32> {
+    Ok(match strength {
+        "none" => 0.0,
+        "x-weak" => 0.1,
+        "weak" => 0.25,
+        "medium" => 0.5,
+        "strong" => 0.75,
+        "x-strong" => 1.0,
+        _ => bail!("invalid break strength {}", strength),
+    })
+}
+
+/// Parse a prosody rate to a speed multiplier
+fn parse_rate(rate: &str) -> Result<f32> {
+    let rate = rate.trim();
+    let speed = match rate {
+        "x-slow" => 0.5,
+        "slow" => 0.75,
+        "medium" | "default" => 1.0,
+        "fast" => 1.25,
+        "x-fast" => 1.75,
+        _ => {
+            if let Some(percent) = rate.strip_suffix('%') {
+                let value = percent.parse::<f32>()?;
+                if percent.starts_with('+') || percent.starts_with('-') {
+                    1.0 + value / 100.0
+                } else {
+                    value / 100.0
+                }
+            } else {
+                rate.parse::<f32>()?
+            }
+        }
+    };
+    if speed <= 0.0 {
+        bail!("invalid prosody rate {}", rate);
+    }
+    Ok(speed)
+}
+
+/// Synthesize SSML, joining the pieces with the requested silences.
+pub fn synthesize_ssml(
+    tts: &mut dyn TextToSpeech,
+    ssml: &str,
+    options: &SsmlOptions,
+) -> Result<TtsAudio> {
+    let parts = parse_ssml(ssml, options)?;
+    let sample_rate = tts.sample_rate();
+
+    let mut samples = Vec::new();
+    for part in parts {
+        match part {
+            SsmlPart::Text { text, sid, speed } => {
+                let audio = tts.synthesize(&text, sid, speed)?;
+                samples.extend_from_slice(&audio.samples);
+            }
+            SsmlPart::Break(seconds) => {
+                let len = (seconds * sample_rate as f32) as usize;
+                samples.resize(samples.len() + len, 0.0);
+            }
+        }
+    }
+    let duration = (samples.len() as u32 / sample_rate.max(1)) as i32;
+
+    Ok(TtsAudio {
+        samples,
+        sample_rate,
+        duration,
+    })
+}
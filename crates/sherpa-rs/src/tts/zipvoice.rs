@@ -1,13 +1,156 @@
-use std::{mem, ptr::null};
+use std::{collections::HashMap, mem, ptr::null};
 
-use crate::{utils::cstring_from_str, OnnxConfig};
-use eyre::Result;
+use crate::{read_audio_file_with_options, utils::cstring_from_str, OnnxConfig, ReadAudioOptions};
+use eyre::{bail, Result};
 use sherpa_rs_sys;
 
-use super::{CommonTtsConfig, TtsAudio};
+use super::{long_text::split_line, CommonTtsConfig, TextToSpeech, TtsAudio};
 
 pub struct ZipVoiceTts {
     tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts,
+    prompt: Option<ZipVoicePrompt>,
+    voices: ZipVoicePromptStore,
+    num_steps: i32,
+}
+
+/// Reference audio and its transcript used to clone a voice.
+#[derive(Debug, Clone, Default)]
+pub struct ZipVoicePrompt {
+    pub text: String,
+    pub samples: Vec<f32>,
+    pub sample_rate: i32,
+}
+
+/// Named prompts kept at the sample rate of the model, so they're loaded, resampled
+/// and validated once instead of on every call.
+#[derive(Debug, Clone)]
+pub struct ZipVoicePromptStore {
+    sample_rate: u32,
+    prompts: HashMap<String, ZipVoicePrompt>,
+}
+
+impl ZipVoicePromptStore {
+    /// Longer prompts work but slow down every synthesis
+    const MAX_RECOMMENDED_SECONDS: f32 = 20.0;
+
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            prompts: HashMap::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Load a prompt from a wav file and a text file with its transcript.
+    pub fn load(&mut self, name: &str, audio_path: &str, transcript_path: &str) -> Result<()> {
+        let text = std::fs::read_to_string(transcript_path)?;
+        let options = ReadAudioOptions {
+            sample_rate: Some(self.sample_rate),
+            ..Default::default()
+        };
+        let (samples, sample_rate) = read_audio_file_with_options(audio_path, options)?;
+        self.insert(
+            name,
+            ZipVoicePrompt {
+                text,
+                samples,
+                sample_rate: sample_rate as i32,
+            },
+        )
+    }
+
+    /// Load every `<name>.wav` in `dir` that has a `<name>.txt` transcript next to it.
+    /// Returns the loaded names.
+    pub fn load_dir(&mut self, dir: &str) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+            {
+                continue;
+            }
+            let transcript = path.with_extension("txt");
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if !transcript.exists() {
+                tracing::warn!("skipping {} without transcript", path.display());
+                continue;
+            }
+            self.load(name, &path.to_string_lossy(), &transcript.to_string_lossy())?;
+            names.push(name.to_string());
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Add a prompt, resampling it to the store sample rate if needed.
+    pub fn insert(&mut self, name: &str, mut prompt: ZipVoicePrompt) -> Result<()> {
+        prompt.text = prompt.text.trim().to_string();
+        if prompt.text.is_empty() {
+            bail!("prompt {} has an empty transcript", name);
+        }
+        if prompt.sample_rate <= 0 {
+            bail!(
+                "prompt {} has invalid sample rate {}",
+                name,
+                prompt.sample_rate
+            );
+        }
+        if prompt.samples.iter().all(|&sample| sample == 0.0) {
+            bail!("prompt {} has no audio", name);
+        }
+        if prompt.sample_rate as u32 != self.sample_rate {
+            prompt.samples = crate::resampler::resample(
+                &prompt.samples,
+                prompt.sample_rate as u32,
+                self.sample_rate,
+            )?;
+            prompt.sample_rate = self.sample_rate as i32;
+        }
+        let seconds = prompt.samples.len() as f32 / self.sample_rate as f32;
+        if seconds > Self::MAX_RECOMMENDED_SECONDS {
+            tracing::warn!(
+                "prompt {} is {:.1}s long, prefer shorter prompts",
+                name,
+                seconds
+            );
+        }
+        self.prompts.insert(name.to_string(), prompt);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ZipVoicePrompt> {
+        self.prompts.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ZipVoicePrompt> {
+        self.prompts.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.prompts.contains_key(name)
+    }
+
+    /// Sorted names of the stored prompts
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self.prompts.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.prompts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty()
+    }
 }
 
 #[derive(Default)]
@@ -71,7 +214,62 @@ impl ZipVoiceTts {
             sherpa_rs_sys::SherpaOnnxCreateOfflineTts(&config)
         };
 
-        Self { tts }
+        let sample_rate = if tts.is_null() {
+            0
+        } else {
+            unsafe { super::sample_rate(tts) }
+        };
+        Self {
+            tts,
+            prompt: None,
+            voices: ZipVoicePromptStore::new(sample_rate),
+            num_steps: 4,
+        }
+    }
+
+    /// Sample rate of the generated audio
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { super::sample_rate(self.tts) }
+    }
+
+    pub fn num_speakers(&self) -> i32 {
+        unsafe { super::num_speakers(self.tts) }
+    }
+
+    /// Set the prompt used by [`TextToSpeech::synthesize`].
+    pub fn set_prompt(&mut self, prompt: ZipVoicePrompt) {
+        self.prompt = Some(prompt);
+    }
+
+    /// Named prompts used by [`ZipVoiceTts::create_with_voice`]
+    pub fn voices(&self) -> &ZipVoicePromptStore {
+        &self.voices
+    }
+
+    pub fn voices_mut(&mut self) -> &mut ZipVoicePromptStore {
+        &mut self.voices
+    }
+
+    /// Synthesize with a prompt from the voice store, eg. `create_with_voice(text, "alice", 1.0)`.
+    pub fn create_with_voice(&mut self, text: &str, voice: &str, speed: f32) -> Result<TtsAudio> {
+        let Some(prompt) = self.voices.prompts.get(voice) else {
+            bail!("unknown voice {}", voice);
+        };
+        generate(
+            self.tts,
+            text,
+            &prompt.text,
+            &prompt.samples,
+            prompt.sample_rate,
+            speed,
+            self.num_steps,
+        )
+    }
+
+    /// Set the number of inference steps used by [`TextToSpeech::synthesize`] and
+    /// [`ZipVoiceTts::create_with_voice`].
+    pub fn set_num_steps(&mut self, num_steps: i32) {
+        self.num_steps = num_steps;
     }
 
     pub fn create(
@@ -83,47 +281,162 @@ impl ZipVoiceTts {
         speed: f32,
         num_steps: i32,
     ) -> Result<TtsAudio> {
-        unsafe {
-            let text_cstr = cstring_from_str(text);
-            let prompt_text_cstr = cstring_from_str(prompt_text);
-
-            let audio_ptr = sherpa_rs_sys::SherpaOnnxOfflineTtsGenerateWithZipvoice(
-                self.tts,
-                text_cstr.as_ptr(),
-                prompt_text_cstr.as_ptr(),
-                prompt_samples.as_ptr(),
-                prompt_samples.len() as i32,
-                prompt_sr,
-                speed,
-                num_steps,
-            );
+        generate(
+            self.tts,
+            text,
+            prompt_text,
+            prompt_samples,
+            prompt_sr,
+            speed,
+            num_steps,
+        )
+    }
 
-            if audio_ptr.is_null() {
-                eyre::bail!("audio is null");
-            }
-            let audio = audio_ptr.read();
+    /// Like `create` but synthesizes sentence by sentence and passes each one to `callback`
+    /// with the progress in range [0, 1]. Return `false` from the callback to stop.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_with_callback<F: FnMut(&[f32], f32) -> bool>(
+        &mut self,
+        text: &str,
+        prompt_text: &str,
+        prompt_samples: &[f32],
+        prompt_sr: i32,
+        speed: f32,
+        num_steps: i32,
+        mut callback: F,
+    ) -> Result<TtsAudio> {
+        generate_sentences(
+            self.tts,
+            text,
+            prompt_text,
+            prompt_samples,
+            prompt_sr,
+            speed,
+            num_steps,
+            &mut callback,
+        )
+    }
+}
 
-            if audio.n.is_negative() {
-                eyre::bail!("no samples found");
-            }
-            if audio.samples.is_null() {
-                eyre::bail!("audio samples are null");
-            }
-            let samples: &[f32] = std::slice::from_raw_parts(audio.samples, audio.n as usize);
-            let samples = samples.to_vec();
-            let sample_rate = audio.sample_rate;
-            let duration = (samples.len() as i32) / sample_rate;
+/// Takes the handle instead of `&mut ZipVoiceTts`, so a prompt stored in the struct can
+/// be borrowed at the same time.
+fn generate(
+    tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts,
+    text: &str,
+    prompt_text: &str,
+    prompt_samples: &[f32],
+    prompt_sr: i32,
+    speed: f32,
+    num_steps: i32,
+) -> Result<TtsAudio> {
+    unsafe {
+        let text_cstr = cstring_from_str(text);
+        let prompt_text_cstr = cstring_from_str(prompt_text);
 
-            // Free
-            sherpa_rs_sys::SherpaOnnxDestroyOfflineTtsGeneratedAudio(audio_ptr);
+        let audio_ptr = sherpa_rs_sys::SherpaOnnxOfflineTtsGenerateWithZipvoice(
+            tts,
+            text_cstr.as_ptr(),
+            prompt_text_cstr.as_ptr(),
+            prompt_samples.as_ptr(),
+            prompt_samples.len() as i32,
+            prompt_sr,
+            speed,
+            num_steps,
+        );
+        super::read_generated_audio(audio_ptr)
+    }
+}
 
-            Ok(TtsAudio {
-                samples,
-                sample_rate: sample_rate as u32,
-                duration,
-            })
+#[allow(clippy::too_many_arguments)]
+fn generate_sentences(
+    tts: *const sherpa_rs_sys::SherpaOnnxOfflineTts,
+    text: &str,
+    prompt_text: &str,
+    prompt_samples: &[f32],
+    prompt_sr: i32,
+    speed: f32,
+    num_steps: i32,
+    callback: &mut dyn FnMut(&[f32], f32) -> bool,
+) -> Result<TtsAudio> {
+    let sentences: Vec<&str> = text.lines().flat_map(split_line).collect();
+    let mut samples = Vec::new();
+    let mut sample_rate = 0;
+    for (i, sentence) in sentences.iter().enumerate() {
+        let audio = generate(
+            tts,
+            sentence,
+            prompt_text,
+            prompt_samples,
+            prompt_sr,
+            speed,
+            num_steps,
+        )?;
+        sample_rate = audio.sample_rate;
+        samples.extend_from_slice(&audio.samples);
+
+        let progress = (i + 1) as f32 / sentences.len() as f32;
+        if !callback(&audio.samples, progress) {
+            break;
         }
     }
+    if sample_rate == 0 {
+        bail!("no text to synthesize");
+    }
+    let duration = (samples.len() as u32 / sample_rate) as i32;
+
+    Ok(TtsAudio {
+        samples,
+        sample_rate,
+        duration,
+    })
+}
+
+/// Uses the prompt set with [`ZipVoiceTts::set_prompt`], `sid` is ignored.
+impl TextToSpeech for ZipVoiceTts {
+    fn synthesize(&mut self, text: &str, _sid: i32, speed: f32) -> Result<TtsAudio> {
+        let Some(prompt) = &self.prompt else {
+            bail!("no prompt set, call set_prompt first");
+        };
+        generate(
+            self.tts,
+            text,
+            &prompt.text,
+            &prompt.samples,
+            prompt.sample_rate,
+            speed,
+            self.num_steps,
+        )
+    }
+
+    fn synthesize_with_callback(
+        &mut self,
+        text: &str,
+        _sid: i32,
+        speed: f32,
+        callback: &mut dyn FnMut(&[f32], f32) -> bool,
+    ) -> Result<TtsAudio> {
+        let Some(prompt) = &self.prompt else {
+            bail!("no prompt set, call set_prompt first");
+        };
+        generate_sentences(
+            self.tts,
+            text,
+            &prompt.text,
+            &prompt.samples,
+            prompt.sample_rate,
+            speed,
+            self.num_steps,
+            callback,
+        )
+    }
+
+    fn sample_rate(&self) -> u32 {
+        ZipVoiceTts::sample_rate(self)
+    }
+
+    fn num_speakers(&self) -> i32 {
+        ZipVoiceTts::num_speakers(self)
+    }
 }
 
 unsafe impl Send for ZipVoiceTts {}
@@ -11,3 +11,14 @@ pub unsafe fn cstr_to_string(ptr: *const c_char) -> String {
         std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
     }
 }
+
+/// Scale to unit length so dot products are cosine similarities
+pub fn normalize_embedding(mut embedding: Vec<f32>) -> Vec<f32> {
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in &mut embedding {
+            *v /= norm;
+        }
+    }
+    embedding
+}
@@ -0,0 +1,423 @@
+use eyre::{bail, Result};
+use std::collections::VecDeque;
+
+use crate::{
+    silero_vad::{SileroVad, SileroVadConfig},
+    ten_vad::{TenVad, TenVadConfig},
+};
+
+#[derive(Debug, Clone)]
+pub struct SpeechSegment {
+    pub start: i32,
+    pub samples: Vec<f32>,
+}
+
+/// Common interface implemented by every voice activity detector.
+pub trait VoiceActivityDetector: Send {
+    fn accept_waveform(&mut self, samples: Vec<f32>);
+    fn is_empty(&mut self) -> bool;
+    fn front(&mut self) -> SpeechSegment;
+    fn pop(&mut self);
+    fn flush(&mut self);
+    fn is_speech(&mut self) -> bool;
+    fn clear(&mut self);
+    fn sample_rate(&self) -> u32;
+    fn window_size(&self) -> usize;
+}
+
+impl<V: VoiceActivityDetector + ?Sized> VoiceActivityDetector for Box<V> {
+    fn accept_waveform(&mut self, samples: Vec<f32>) {
+        (**self).accept_waveform(samples)
+    }
+
+    fn is_empty(&mut self) -> bool {
+        (**self).is_empty()
+    }
+
+    fn front(&mut self) -> SpeechSegment {
+        (**self).front()
+    }
+
+    fn pop(&mut self) {
+        (**self).pop()
+    }
+
+    fn flush(&mut self) {
+        (**self).flush()
+    }
+
+    fn is_speech(&mut self) -> bool {
+        (**self).is_speech()
+    }
+
+    fn clear(&mut self) {
+        (**self).clear()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        (**self).sample_rate()
+    }
+
+    fn window_size(&self) -> usize {
+        (**self).window_size()
+    }
+}
+
+#[derive(Debug)]
+pub enum VadConfig {
+    Silero(SileroVadConfig),
+    Ten(TenVadConfig),
+}
+
+impl VadConfig {
+    /// Buffer large enough to hold the longest segment the model can emit.
+    pub(crate) fn buffer_size_in_seconds(&self) -> f32 {
+        let max_speech_duration = match self {
+            VadConfig::Silero(config) => config.max_speech_duration,
+            VadConfig::Ten(config) => config.max_speech_duration,
+        };
+        (max_speech_duration * 2.0).max(30.0)
+    }
+}
+
+/// Create a detector from config so the model can be picked at runtime.
+pub fn create(
+    config: VadConfig,
+    buffer_size_in_seconds: f32,
+) -> Result<Box<dyn VoiceActivityDetector>> {
+    let vad: Box<dyn VoiceActivityDetector> = match config {
+        VadConfig::Silero(config) => Box::new(SileroVad::new(config, buffer_size_in_seconds)?),
+        VadConfig::Ten(config) => Box::new(TenVad::new(config, buffer_size_in_seconds)?),
+    };
+    Ok(vad)
+}
+
+/// Speech region found by [`VadSegmenter`], positioned in the input stream.
+#[derive(Debug, Clone)]
+pub struct VadSegment {
+    /// First sample of the segment
+    pub start: usize,
+    /// One past the last sample of the segment
+    pub end: usize,
+    pub start_sec: f32,
+    pub end_sec: f32,
+    pub samples: Vec<f32>,
+}
+
+/// Iterator that feeds audio into a detector window by window and yields speech segments.
+///
+/// The trailing partial window is padded with silence and the detector is flushed
+/// once the source is exhausted, so every segment is returned.
+pub struct VadSegmenter<V, I> {
+    vad: V,
+    source: I,
+    window: Vec<f32>,
+    ready: VecDeque<VadSegment>,
+    num_samples: usize,
+    finished: bool,
+}
+
+impl<I: Iterator<Item = f32>> VadSegmenter<Box<dyn VoiceActivityDetector>, I> {
+    pub fn new<S: IntoIterator<IntoIter = I>>(config: VadConfig, source: S) -> Result<Self> {
+        let buffer_size_in_seconds = config.buffer_size_in_seconds();
+        let vad = create(config, buffer_size_in_seconds)?;
+        Self::from_vad(vad, source)
+    }
+}
+
+impl<V: VoiceActivityDetector, I: Iterator<Item = f32>> VadSegmenter<V, I> {
+    /// Fails if the window size of `vad` is 0.
+    pub fn from_vad<S: IntoIterator<IntoIter = I>>(vad: V, source: S) -> Result<Self> {
+        let window_size = vad.window_size();
+        if window_size == 0 {
+            bail!("VAD window size must be greater than 0");
+        }
+        Ok(Self {
+            vad,
+            source: source.into_iter(),
+            window: Vec::with_capacity(window_size),
+            ready: VecDeque::new(),
+            num_samples: 0,
+            finished: false,
+        })
+    }
+
+    pub fn into_inner(self) -> V {
+        self.vad
+    }
+
+    fn collect_segments(&mut self) {
+        let sample_rate = self.vad.sample_rate() as f32;
+        while !self.vad.is_empty() {
+            let mut segment = self.vad.front();
+            self.vad.pop();
+
+            let start = segment.start.max(0) as usize;
+            // Drop the silence padded after the end of the source
+            let end = (start + segment.samples.len()).min(self.num_samples);
+            segment.samples.truncate(end.saturating_sub(start));
+
+            self.ready.push_back(VadSegment {
+                start,
+                end,
+                start_sec: start as f32 / sample_rate,
+                end_sec: end as f32 / sample_rate,
+                samples: segment.samples,
+            });
+        }
+    }
+
+    fn feed_window(&mut self) {
+        let window_size = self.vad.window_size();
+        self.window.clear();
+        self.window.extend(self.source.by_ref().take(window_size));
+        self.num_samples += self.window.len();
+
+        if self.window.len() == window_size {
+            self.vad.accept_waveform(self.window.clone());
+        } else {
+            if !self.window.is_empty() {
+                self.window.resize(window_size, 0.0);
+                self.vad.accept_waveform(self.window.clone());
+            }
+            self.vad.flush();
+            self.finished = true;
+        }
+        self.collect_segments();
+    }
+}
+
+impl<V: VoiceActivityDetector, I: Iterator<Item = f32>> Iterator for VadSegmenter<V, I> {
+    type Item = VadSegment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(segment) = self.ready.pop_front() {
+                return Some(segment);
+            }
+            if self.finished {
+                return None;
+            }
+            self.feed_window();
+        }
+    }
+}
+
+/// Maps positions in silence-removed audio back to the original recording.
+#[derive(Debug, Clone, Default)]
+pub struct TimeMap {
+    pub sample_rate: u32,
+    pub spans: Vec<TimeMapSpan>,
+}
+
+/// Run of samples copied unchanged from the original audio.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeMapSpan {
+    pub compressed_start: usize,
+    pub original_start: usize,
+    pub len: usize,
+}
+
+impl TimeMap {
+    pub fn to_original_sample(&self, sample: usize) -> usize {
+        let index = self
+            .spans
+            .partition_point(|span| span.compressed_start <= sample);
+        if index == 0 {
+            return self
+                .spans
+                .first()
+                .map(|span| span.original_start)
+                .unwrap_or(sample);
+        }
+        let span = &self.spans[index - 1];
+        let offset = sample - span.compressed_start;
+        if offset < span.len || index == self.spans.len() {
+            span.original_start + offset
+        } else {
+            // Gap inserted between speech regions, snap to the end of the previous region
+            span.original_start + span.len
+        }
+    }
+
+    pub fn to_original(&self, seconds: f32) -> f32 {
+        let sample_rate = self.sample_rate as f32;
+        let sample = (seconds.max(0.0) * sample_rate).round() as usize;
+        self.to_original_sample(sample) as f32 / sample_rate
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SilenceRemoved {
+    pub samples: Vec<f32>,
+    pub time_map: TimeMap,
+}
+
+/// Keep only the speech regions of `samples`, separated by `gap_seconds` of silence.
+pub fn remove_silence(
+    config: VadConfig,
+    samples: &[f32],
+    gap_seconds: f32,
+) -> Result<SilenceRemoved> {
+    let segmenter = VadSegmenter::new(config, samples.iter().copied())?;
+    let sample_rate = segmenter.vad.sample_rate();
+    let gap = vec![0.0; (gap_seconds.max(0.0) * sample_rate as f32) as usize];
+
+    let mut compressed = Vec::new();
+    let mut spans = Vec::new();
+    for segment in segmenter {
+        if !spans.is_empty() {
+            compressed.extend_from_slice(&gap);
+        }
+        spans.push(TimeMapSpan {
+            compressed_start: compressed.len(),
+            original_start: segment.start,
+            len: segment.samples.len(),
+        });
+        compressed.extend_from_slice(&segment.samples);
+    }
+
+    Ok(SilenceRemoved {
+        samples: compressed,
+        time_map: TimeMap { sample_rate, spans },
+    })
+}
+
+pub(crate) fn create_raw(
+    config: &sherpa_rs_sys::SherpaOnnxVadModelConfig,
+    buffer_size_in_seconds: f32,
+) -> Result<*const sherpa_rs_sys::SherpaOnnxVoiceActivityDetector> {
+    let vad = unsafe {
+        sherpa_rs_sys::SherpaOnnxCreateVoiceActivityDetector(config, buffer_size_in_seconds)
+    };
+    if vad.is_null() {
+        bail!("Failed to create voice activity detector");
+    }
+    Ok(vad)
+}
+
+/// # Safety
+///
+/// `vad` must be a valid detector created by `create_raw`
+pub(crate) unsafe fn front(
+    vad: *const sherpa_rs_sys::SherpaOnnxVoiceActivityDetector,
+) -> SpeechSegment {
+    let segment_ptr = sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorFront(vad);
+    let raw_segment = segment_ptr.read();
+    let samples: &[f32] = std::slice::from_raw_parts(raw_segment.samples, raw_segment.n as usize);
+
+    let segment = SpeechSegment {
+        samples: samples.to_vec(),
+        start: raw_segment.start,
+    };
+
+    // Free
+    sherpa_rs_sys::SherpaOnnxDestroySpeechSegment(segment_ptr);
+
+    segment
+}
+
+/// # Safety
+///
+/// `vad` must be a valid detector created by `create_raw`
+pub(crate) unsafe fn accept_waveform(
+    vad: *const sherpa_rs_sys::SherpaOnnxVoiceActivityDetector,
+    samples: &[f32],
+) {
+    sherpa_rs_sys::SherpaOnnxVoiceActivityDetectorAcceptWaveform(
+        vad,
+        samples.as_ptr(),
+        samples.len().try_into().unwrap(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Detects windows with any non zero sample as speech
+    struct StubVad {
+        window_size: usize,
+        offset: usize,
+        current: Option<SpeechSegment>,
+        segments: VecDeque<SpeechSegment>,
+    }
+
+    impl StubVad {
+        fn new(window_size: usize) -> Self {
+            Self {
+                window_size,
+                offset: 0,
+                current: None,
+                segments: VecDeque::new(),
+            }
+        }
+    }
+
+    impl VoiceActivityDetector for StubVad {
+        fn accept_waveform(&mut self, samples: Vec<f32>) {
+            let len = samples.len();
+            if samples.iter().any(|&sample| sample != 0.0) {
+                let offset = self.offset as i32;
+                self.current
+                    .get_or_insert_with(|| SpeechSegment {
+                        start: offset,
+                        samples: Vec::new(),
+                    })
+                    .samples
+                    .extend(samples);
+            } else {
+                self.flush();
+            }
+            self.offset += len;
+        }
+
+        fn is_empty(&mut self) -> bool {
+            self.segments.is_empty()
+        }
+
+        fn front(&mut self) -> SpeechSegment {
+            self.segments.front().unwrap().clone()
+        }
+
+        fn pop(&mut self) {
+            self.segments.pop_front();
+        }
+
+        fn flush(&mut self) {
+            self.segments.extend(self.current.take());
+        }
+
+        fn is_speech(&mut self) -> bool {
+            self.current.is_some()
+        }
+
+        fn clear(&mut self) {
+            self.segments.clear();
+        }
+
+        fn sample_rate(&self) -> u32 {
+            4
+        }
+
+        fn window_size(&self) -> usize {
+            self.window_size
+        }
+    }
+
+    #[test]
+    fn rejects_zero_window_size() {
+        assert!(VadSegmenter::from_vad(StubVad::new(0), vec![1.0]).is_err());
+    }
+
+    #[test]
+    fn segments_stream() {
+        let samples = [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0];
+        let segments: Vec<_> = VadSegmenter::from_vad(StubVad::new(2), samples)
+            .unwrap()
+            .map(|segment| (segment.start, segment.end, segment.samples))
+            .collect();
+        // The last segment is flushed without the silence padding the partial window
+        assert_eq!(segments, [(2, 6, vec![1.0; 4]), (8, 9, vec![1.0])]);
+    }
+}
@@ -0,0 +1,36 @@
+/*
+Remove noise from a recording, then detect speech in the clean audio
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speech-enhancement-models/gtcrn_simple.onnx
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speech-enhancement-models/inp_16k.wav
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/silero_vad.onnx
+cargo run --example denoise inp_16k.wav
+*/
+use sherpa_rs::{
+    denoise::{Denoiser, DenoiserConfig},
+    silero_vad::SileroVadConfig,
+    vad::{VadConfig, VadSegmenter},
+};
+
+fn main() {
+    let file_path = std::env::args().nth(1).expect("Missing file path argument");
+    let (samples, sample_rate) = sherpa_rs::read_audio_file(&file_path).unwrap();
+
+    let config = DenoiserConfig {
+        model: "gtcrn_simple.onnx".into(),
+        ..Default::default()
+    };
+    let mut denoiser = Denoiser::new(config).unwrap();
+    let samples = denoiser.denoise_to(&samples, sample_rate, 16000).unwrap();
+    sherpa_rs::write_audio_file("denoised.wav", &samples, 16000).unwrap();
+    println!("Created denoised.wav");
+
+    let config = SileroVadConfig {
+        model: "silero_vad.onnx".into(),
+        window_size: 512,
+        ..Default::default()
+    };
+    for segment in VadSegmenter::new(VadConfig::Silero(config), samples).unwrap() {
+        println!("speech {:.2}s - {:.2}s", segment.start_sec, segment.end_sec);
+    }
+}
@@ -26,18 +26,25 @@ fn main() {
         ..Default::default()
     };
 
-    let progress_callback = |n_computed_chunks: i32, n_total_chunks: i32| -> i32 {
-        let progress = 100 * n_computed_chunks / n_total_chunks;
-        println!("🗣️ Diarizing... {}% 🎯", progress);
-        0
-    };
-
     let mut sd =
         sherpa_rs::diarize::Diarize::new(segment_model_path, embedding_model_path, config).unwrap();
-    let (samples, _) = sherpa_rs::read_audio_file(wav_path).unwrap();
+    let options = sherpa_rs::ReadAudioOptions {
+        sample_rate: Some(sd.sample_rate()),
+        ..Default::default()
+    };
+    let (samples, _) = sherpa_rs::read_audio_file_with_options(wav_path, options).unwrap();
 
+    // The callback can borrow local state, return false to stop
+    let mut last_progress = -1;
     let segments = sd
-        .compute(samples, Some(Box::new(progress_callback)))
+        .compute_with_callback(&samples, None, |n_computed_chunks, n_total_chunks| {
+            let progress = 100 * n_computed_chunks / n_total_chunks;
+            if progress != last_progress {
+                println!("🗣️ Diarizing... {}% 🎯", progress);
+                last_progress = progress;
+            }
+            true
+        })
         .unwrap();
     for segment in segments {
         println!(
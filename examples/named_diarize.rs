@@ -0,0 +1,73 @@
+/*
+Diarize audio file and label the speakers with the names of enrolled people.
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/sherpa-onnx-pyannote-segmentation-3-0.tar.bz2
+tar xvf sherpa-onnx-pyannote-segmentation-3-0.tar.bz2
+rm sherpa-onnx-pyannote-segmentation-3-0.tar.bz2
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/nemo_en_speakerverification_speakernet.onnx
+wget https://github.com/thewh1teagle/sherpa-rs/releases/download/v0.1.0/biden.wav -O biden.wav
+wget https://github.com/thewh1teagle/sherpa-rs/releases/download/v0.1.0/obama.wav -O obama.wav
+
+cargo run --example named_diarize ./sherpa-onnx-pyannote-segmentation-3-0/model.onnx ./nemo_en_speakerverification_speakernet.onnx meeting.wav biden=biden.wav obama=obama.wav
+*/
+use sherpa_rs::{
+    diarize::DiarizeConfig,
+    embedding_manager::EmbeddingManager,
+    named_diarize::{NamedDiarize, NamedDiarizeConfig},
+    speaker_id::{EmbeddingExtractor, ExtractorConfig},
+    ReadAudioOptions,
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (segment_model_path, embedding_model_path, wav_path) = (
+        args.get(1)
+            .expect("Missing path argument for segmentation model"),
+        args.get(2)
+            .expect("Missing path argument for embedding model"),
+        args.get(3).expect("Missing path argument for wav file"),
+    );
+
+    let config = NamedDiarizeConfig {
+        force_num_clusters: true,
+        ..Default::default()
+    };
+    let mut sd = NamedDiarize::new(
+        segment_model_path,
+        embedding_model_path,
+        DiarizeConfig::default(),
+        config,
+    )
+    .unwrap();
+    let options = ReadAudioOptions {
+        sample_rate: Some(sd.sample_rate()),
+        ..Default::default()
+    };
+
+    // Enroll people from name=wav arguments, with the same embedding model
+    let mut extractor = EmbeddingExtractor::new(ExtractorConfig {
+        model: embedding_model_path.clone(),
+        ..Default::default()
+    })
+    .unwrap();
+    let mut enrolled = EmbeddingManager::new(extractor.embedding_size as i32);
+    for arg in &args[4..] {
+        let (name, path) = arg.split_once('=').expect("Expected name=wav");
+        let (samples, sample_rate) =
+            sherpa_rs::read_audio_file_with_options(path, options.clone()).unwrap();
+        let mut embedding = extractor
+            .compute_speaker_embedding(samples, sample_rate)
+            .unwrap();
+        enrolled.add(name.into(), &mut embedding).unwrap();
+    }
+
+    let (samples, _) = sherpa_rs::read_audio_file_with_options(wav_path, options).unwrap();
+    let segments = sd.compute(&samples, &mut enrolled).unwrap();
+    for segment in segments {
+        println!(
+            "start = {} end = {} speaker = {} ({})",
+            segment.start, segment.end, segment.name, segment.speaker
+        );
+    }
+}
@@ -0,0 +1,55 @@
+/*
+Label speakers while the audio streams in, eg. for live captions
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/silero_vad.onnx
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/3dspeaker_speech_eres2net_base_sv_zh-cn_3dspeaker_16k.onnx
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/0-four-speakers-zh.wav
+cargo run --example online_diarize 0-four-speakers-zh.wav
+*/
+use sherpa_rs::{
+    chunk_reader::ChunkReader,
+    online_diarize::{OnlineDiarize, OnlineDiarizeConfig},
+    silero_vad::SileroVadConfig,
+    speaker_id::ExtractorConfig,
+    vad::VadConfig,
+    ReadAudioOptions,
+};
+
+fn main() {
+    let file_path = std::env::args().nth(1).expect("Missing file path argument");
+
+    let config = OnlineDiarizeConfig {
+        vad: VadConfig::Silero(SileroVadConfig {
+            model: "silero_vad.onnx".into(),
+            min_silence_duration: 0.3,
+            min_speech_duration: 0.25,
+            max_speech_duration: 10.0,
+            ..Default::default()
+        }),
+        extractor: ExtractorConfig {
+            model: "3dspeaker_speech_eres2net_base_sv_zh-cn_3dspeaker_16k.onnx".into(),
+            ..Default::default()
+        },
+        threshold: 0.5,
+        ..Default::default()
+    };
+    let mut diarize = OnlineDiarize::new(config).unwrap();
+
+    // 100ms chunks, as they would come from a microphone
+    let reader = ChunkReader::open(&file_path, 1600, ReadAudioOptions::default()).unwrap();
+    for chunk in reader {
+        for segment in diarize.accept_waveform(&chunk.unwrap()) {
+            println!(
+                "start = {:.2} end = {:.2} speaker = {}",
+                segment.start, segment.end, segment.speaker
+            );
+        }
+    }
+    for segment in diarize.flush() {
+        println!(
+            "start = {:.2} end = {:.2} speaker = {}",
+            segment.start, segment.end, segment.speaker
+        );
+    }
+    println!("Found {} speakers", diarize.num_speakers());
+}
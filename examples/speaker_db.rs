@@ -0,0 +1,69 @@
+/*
+Enroll speakers into a database file, then identify speakers with it after a restart
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/nemo_en_speakerverification_speakernet.onnx
+wget https://github.com/thewh1teagle/sherpa-rs/releases/download/v0.1.0/biden.wav -O biden.wav
+wget https://github.com/thewh1teagle/sherpa-rs/releases/download/v0.1.0/obama.wav -O obama.wav
+cargo run --example speaker_db enroll biden=biden.wav obama=obama.wav
+cargo run --example speaker_db identify obama.wav
+*/
+use sherpa_rs::{
+    embedding_manager::EmbeddingManager,
+    speaker_db::ModelIdentity,
+    speaker_id::{EmbeddingExtractor, ExtractorConfig, DEFAULT_SIMILARITY_THRESHOLD},
+};
+
+const MODEL: &str = "nemo_en_speakerverification_speakernet.onnx";
+const DB_PATH: &str = "speakers.db";
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let command = args.get(1).expect("Missing command, enroll or identify");
+
+    let config = ExtractorConfig {
+        model: MODEL.into(),
+        ..Default::default()
+    };
+    let mut extractor = EmbeddingExtractor::new(config).unwrap();
+    // Refuses databases created with another model
+    let model = ModelIdentity::from_model(MODEL, extractor.embedding_size).unwrap();
+    let mut manager = if std::path::Path::new(DB_PATH).exists() {
+        EmbeddingManager::load(DB_PATH, &model).unwrap()
+    } else {
+        EmbeddingManager::new(extractor.embedding_size as i32)
+    };
+
+    let mut embed = |path: &str| {
+        let (samples, sample_rate) = sherpa_rs::read_audio_file(path).unwrap();
+        extractor
+            .compute_speaker_embedding(samples, sample_rate)
+            .unwrap()
+    };
+
+    match command.as_str() {
+        "enroll" => {
+            for arg in &args[2..] {
+                let (name, path) = arg.split_once('=').expect("Expected name=wav");
+                // Keep earlier recordings of the speaker
+                let mut embeddings = manager
+                    .embeddings(name)
+                    .map(|embeddings| embeddings.to_vec())
+                    .unwrap_or_default();
+                embeddings.push(embed(path));
+                manager.remove(name);
+                manager.add_list(name.into(), &embeddings).unwrap();
+            }
+            manager.save(DB_PATH, &model).unwrap();
+        }
+        "identify" => {
+            let path = args.get(2).expect("Missing path argument for wav file");
+            let name = manager.search(&embed(path), DEFAULT_SIMILARITY_THRESHOLD);
+            println!("{}: {}", path, name.as_deref().unwrap_or("unknown"));
+        }
+        _ => panic!("Unknown command {}", command),
+    }
+
+    for (name, embeddings) in manager.speakers() {
+        println!("{} ({} embeddings)", name, embeddings.len());
+    }
+}
@@ -0,0 +1,38 @@
+/*
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/tts-models/kokoro-multi-lang-v1_0.tar.bz2
+tar xf kokoro-multi-lang-v1_0.tar.bz2
+rm kokoro-multi-lang-v1_0.tar.bz2
+cargo run --example tts_kokoro_blend --features tts
+*/
+use sherpa_rs::tts::{KokoroTts, KokoroTtsConfig, KokoroVoices};
+
+fn main() {
+    let mut voices = KokoroVoices::open("./kokoro-multi-lang-v1_0/voices.bin").unwrap();
+    voices.set_name(2, "af_bella").unwrap();
+    voices.set_name(11, "am_adam").unwrap();
+    println!("Found {} voices", voices.len());
+
+    // Replace the first voice with the blend and use it as sid 0
+    let style = voices
+        .blend(&[("af_bella", 0.7), ("am_adam", 0.3)])
+        .unwrap();
+    voices.set_style(0, &style).unwrap();
+    voices.set_name(0, "brand").unwrap();
+    voices.write("voices-brand.bin").unwrap();
+
+    let config = KokoroTtsConfig {
+        model: "./kokoro-multi-lang-v1_0/model.onnx".to_string(),
+        tokens: "./kokoro-multi-lang-v1_0/tokens.txt".into(),
+        data_dir: "./kokoro-multi-lang-v1_0/espeak-ng-data".into(),
+        dict_dir: "./kokoro-multi-lang-v1_0/dict".into(),
+        lexicon: "./kokoro-multi-lang-v1_0/lexicon-us-en.txt".into(),
+        ..Default::default()
+    };
+    let mut tts = KokoroTts::with_voices(config, &voices).unwrap();
+
+    let sid = voices.sid("brand").unwrap();
+    let text = "This voice is a blend of Bella and Adam.";
+    let audio = tts.create(text, sid, 1.0).unwrap();
+    sherpa_rs::write_audio_file("audio.wav", &audio.samples, audio.sample_rate).unwrap();
+    println!("Created audio.wav and voices-brand.bin")
+}
@@ -0,0 +1,40 @@
+/*
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/tts-models/kokoro-multi-lang-v1_0.tar.bz2
+tar xf kokoro-multi-lang-v1_0.tar.bz2
+rm kokoro-multi-lang-v1_0.tar.bz2
+cargo run --example tts_long_text --features tts article.txt
+*/
+use sherpa_rs::audio_writer::{AudioWriter, WriteAudioOptions};
+use sherpa_rs::tts::{
+    synthesize_long_text_with_callback, KokoroTts, KokoroTtsConfig, LongTextOptions,
+};
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("Missing text file path argument");
+    let text = std::fs::read_to_string(path).unwrap();
+
+    let config = KokoroTtsConfig {
+        model: "./kokoro-multi-lang-v1_0/model.onnx".to_string(),
+        voices: "./kokoro-multi-lang-v1_0/voices.bin".into(),
+        tokens: "./kokoro-multi-lang-v1_0/tokens.txt".into(),
+        data_dir: "./kokoro-multi-lang-v1_0/espeak-ng-data".into(),
+        dict_dir: "./kokoro-multi-lang-v1_0/dict".into(),
+        lexicon: "./kokoro-multi-lang-v1_0/lexicon-us-en.txt".into(),
+        ..Default::default()
+    };
+    let mut tts = KokoroTts::new(config);
+
+    // Write each chunk as soon as it's ready instead of keeping the whole article in memory
+    let mut writer =
+        AudioWriter::create("audio.wav", tts.sample_rate(), WriteAudioOptions::default()).unwrap();
+    let options = LongTextOptions::default();
+    synthesize_long_text_with_callback(&mut tts, &text, &options, &mut |progress, samples| {
+        println!("[{:>3.0}%] {}", progress.fraction() * 100.0, progress.text);
+        writer.write(samples).is_ok()
+    })
+    .unwrap();
+    writer.finalize().unwrap();
+    println!("Created audio.wav")
+}
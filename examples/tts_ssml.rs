@@ -0,0 +1,37 @@
+/*
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/tts-models/kokoro-multi-lang-v1_0.tar.bz2
+tar xf kokoro-multi-lang-v1_0.tar.bz2
+rm kokoro-multi-lang-v1_0.tar.bz2
+cargo run --example tts_ssml --features tts
+*/
+use sherpa_rs::tts::{synthesize_ssml, KokoroTts, KokoroTtsConfig, SsmlOptions};
+
+fn main() {
+    let config = KokoroTtsConfig {
+        model: "./kokoro-multi-lang-v1_0/model.onnx".to_string(),
+        voices: "./kokoro-multi-lang-v1_0/voices.bin".into(),
+        tokens: "./kokoro-multi-lang-v1_0/tokens.txt".into(),
+        data_dir: "./kokoro-multi-lang-v1_0/espeak-ng-data".into(),
+        dict_dir: "./kokoro-multi-lang-v1_0/dict".into(),
+        lexicon: "./kokoro-multi-lang-v1_0/lexicon-us-en.txt".into(),
+        ..Default::default()
+    };
+    let mut tts = KokoroTts::new(config);
+
+    let mut options = SsmlOptions::default();
+    options.voices.insert("af_bella".into(), 2);
+    options.voices.insert("am_adam".into(), 11);
+
+    let ssml = r#"<speak>
+        <p>
+            <s><voice name="af_bella">Your verification code is <say-as interpret-as="digits">4821</say-as>.</voice></s>
+            <s><voice name="am_adam"><prosody rate="slow">Please do not share it with anyone.</prosody></voice></s>
+        </p>
+        <break time="800ms"/>
+        <prosody rate="fast">Thank you for calling.</prosody>
+    </speak>"#;
+
+    let audio = synthesize_ssml(&mut tts, ssml, &options).unwrap();
+    sherpa_rs::write_audio_file("audio.wav", &audio.samples, audio.sample_rate).unwrap();
+    println!("Created audio.wav")
+}
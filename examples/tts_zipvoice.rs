@@ -4,40 +4,24 @@ tar xf sherpa-onnx-zipvoice-distill-zh-en-emilia.tar.bz2
 rm sherpa-onnx-zipvoice-distill-zh-en-emilia.tar.bz2
 cargo run --example tts_zipvoice
 */
-use hound;
 use sherpa_rs::{
+    read_audio_file_with_options,
     tts::{CommonTtsConfig, ZipVoiceTts, ZipVoiceTtsConfig},
-    OnnxConfig,
+    OnnxConfig, ReadAudioOptions,
 };
 
 fn main() {
     let model_dir = "./sherpa-onnx-zipvoice-distill-zh-en-emilia";
 
-    // Load prompt audio
+    // Load prompt audio at its native sample rate, downmixed to mono
     let prompt_path = format!("{}/prompt.wav", model_dir);
-    let mut reader = hound::WavReader::open(&prompt_path).expect("Failed to open prompt.wav");
-    let spec = reader.spec();
-    let prompt_sr = spec.sample_rate as i32;
-
-    println!("Prompt audio spec: {:?}", spec);
-
-    // Read samples and convert to f32 (mono only for now)
-    let mut prompt_samples: Vec<f32> = if spec.sample_format == hound::SampleFormat::Int {
-        reader
-            .samples::<i16>()
-            .map(|s| s.unwrap() as f32 / 32768.0)
-            .collect()
-    } else {
-        reader.samples::<f32>().map(|s| s.unwrap()).collect()
+    let options = ReadAudioOptions {
+        sample_rate: None,
+        ..Default::default()
     };
-
-    // If stereo, convert to mono by averaging channels
-    if spec.channels == 2 {
-        prompt_samples = prompt_samples
-            .chunks(2)
-            .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
-            .collect();
-    }
+    let (prompt_samples, prompt_sr) =
+        read_audio_file_with_options(&prompt_path, options).expect("Failed to open prompt.wav");
+    let prompt_sr = prompt_sr as i32;
 
     println!(
         "Loaded {} prompt samples at {} Hz",
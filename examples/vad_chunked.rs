@@ -0,0 +1,47 @@
+/*
+Detect speech in a long audio file without loading it into memory
+
+wget https://github.com/k2-fsa/sherpa-onnx/releases/download/asr-models/silero_vad.onnx
+wget https://github.com/thewh1teagle/sherpa-rs/releases/download/v0.1.0/motivation.wav -O motivation.wav
+cargo run --example vad_chunked motivation.wav
+*/
+use sherpa_rs::{
+    chunk_reader::ChunkReader,
+    silero_vad::{SileroVad, SileroVadConfig},
+    ReadAudioOptions,
+};
+
+fn main() {
+    let file_path = std::env::args().nth(1).expect("Missing file path argument");
+
+    let window_size: usize = 512;
+    let reader = ChunkReader::open(&file_path, window_size, ReadAudioOptions::default()).unwrap();
+    let sample_rate = reader.sample_rate();
+
+    let config = SileroVadConfig {
+        model: "silero_vad.onnx".into(),
+        window_size: window_size as i32,
+        ..Default::default()
+    };
+    let mut vad = SileroVad::new(config, 30.0).unwrap();
+
+    for chunk in reader {
+        vad.accept_waveform(chunk.unwrap());
+        while !vad.is_empty() {
+            let segment = vad.front();
+            let start_sec = (segment.start as f32) / sample_rate as f32;
+            let duration_sec = (segment.samples.len() as f32) / sample_rate as f32;
+            println!("start={}s duration={}s", start_sec, duration_sec);
+            vad.pop();
+        }
+    }
+
+    vad.flush();
+    while !vad.is_empty() {
+        let segment = vad.front();
+        let start_sec = (segment.start as f32) / sample_rate as f32;
+        let duration_sec = (segment.samples.len() as f32) / sample_rate as f32;
+        println!("start={}s duration={}s", start_sec, duration_sec);
+        vad.pop();
+    }
+}